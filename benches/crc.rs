@@ -0,0 +1,21 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use pngme::{crc32, crc32_bytewise};
+
+fn bench_crc32(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crc32");
+    for size in [4 * 1024, 4 * 1024 * 1024] {
+        let data: Vec<u8> = (0..size).map(|i| (i * 31 % 251) as u8).collect();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("slice_by_8", size), &data, |b, data| {
+            b.iter(|| crc32(black_box(data)))
+        });
+        group.bench_with_input(BenchmarkId::new("bytewise", size), &data, |b, data| {
+            b.iter(|| crc32_bytewise(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_crc32);
+criterion_main!(benches);
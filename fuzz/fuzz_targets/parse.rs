@@ -0,0 +1,35 @@
+//! Feeds arbitrary bytes to every parser that may see untrusted input. Any panic is a bug;
+//! errors are expected and ignored.
+//!
+//! Run from the repository root with a nightly toolchain and `cargo install cargo-fuzz`:
+//!
+//! ```text
+//! cargo +nightly fuzz run parse
+//! ```
+//!
+//! Crashing inputs are saved under `fuzz/artifacts/parse/` and can be replayed with
+//! `cargo +nightly fuzz run parse <path>`.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pngme::{parse_itxt, parse_text, verify_bytes, Chunk, ImageHeader, Png};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(chunk) = Chunk::try_from(data) {
+        let _ = parse_text(&chunk);
+        let _ = parse_itxt(&chunk);
+        let _ = ImageHeader::from_chunk(&chunk);
+        let _ = pngme::payload::decode(chunk.data(), Some("passphrase"));
+    }
+    let _ = Chunk::from_reader(&mut &data[..]);
+
+    let parsed = Png::try_from(data);
+    let streamed = Png::from_reader(data);
+    assert_eq!(parsed.is_ok(), streamed.is_ok());
+    if let Ok(png) = parsed {
+        assert_eq!(png.as_bytes(), data);
+        let _ = png.dimensions();
+        let _ = png.verify();
+    }
+    let _ = verify_bytes(data);
+});
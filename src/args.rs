@@ -0,0 +1,431 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::chunk_type::{ChunkType, ChunkTypeError};
+use crate::log::Verbosity;
+
+/// Hide secret messages inside PNG files.
+#[derive(Debug, Parser)]
+#[command(name = "pngme", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: PngMeArgs,
+    /// Suppress success messages; errors are still printed
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// Log each chunk that encode, decode, remove, strip, extract, rename or repair acts
+    /// on to stderr
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+}
+
+/// Parses a chunk type argument, either as its four letters (`ruSt`) or as `0x` followed
+/// by the eight hex digits of its bytes (`0x72755374`).
+fn parse_chunk_type(s: &str) -> Result<ChunkType, ChunkTypeError> {
+    let hex = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => hex,
+        None => return s.parse(),
+    };
+    if hex.len() != 8 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ChunkTypeError::InvalidHex(s.to_string()));
+    }
+    let value =
+        u32::from_str_radix(hex, 16).map_err(|_| ChunkTypeError::InvalidHex(s.to_string()))?;
+    ChunkType::try_from(value.to_be_bytes())
+}
+
+impl Cli {
+    /// The verbosity selected by `--quiet` or `--verbose`.
+    pub fn verbosity(&self) -> Verbosity {
+        if self.quiet {
+            Verbosity::Quiet
+        } else if self.verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PngMeArgs {
+    Encode(EncodeArgs),
+    Decode(DecodeArgs),
+    Remove(RemoveArgs),
+    Print(PrintArgs),
+    Strip(StripArgs),
+    Meta(MetaArgs),
+    Count(CountArgs),
+    Verify(VerifyArgs),
+    Find(FindArgs),
+    Diff(DiffArgs),
+    Extract(ExtractArgs),
+    Rename(RenameArgs),
+    Repair(RepairArgs),
+}
+
+/// Hide a message in a PNG file.
+#[derive(Debug, Args)]
+pub struct EncodeArgs {
+    /// PNG file to modify, or - for stdin; with --output-dir, a directory of PNG files
+    pub file_path: PathBuf,
+    /// Type of the chunk that will hold the message, e.g. ruSt or its hex 0x72755374
+    #[arg(value_parser = parse_chunk_type)]
+    pub chunk_type: ChunkType,
+    /// Messages to hide, each in a chunk of its own
+    #[arg(required_unless_present = "message_file")]
+    pub messages: Vec<String>,
+    /// Where to write the result, or - for stdout; defaults to overwriting the input file
+    #[arg(short, long, value_name = "PATH")]
+    pub output_file: Option<PathBuf>,
+    /// Encode every .png file in the FILE_PATH directory, writing the results here
+    #[arg(long, value_name = "DIR", conflicts_with = "output_file")]
+    pub output_dir: Option<PathBuf>,
+    /// Position to insert the chunk at; defaults to just before IEND
+    #[arg(long, visible_alias = "chunk-index")]
+    pub index: Option<usize>,
+    /// Hide the raw bytes of this file instead of a message string
+    #[arg(long, value_name = "PATH", conflicts_with = "messages")]
+    pub message_file: Option<PathBuf>,
+    /// Allow hiding the message in a critical chunk type, which can corrupt the image
+    #[arg(long)]
+    pub force: bool,
+    /// Deflate the message before hiding it; decode detects this automatically
+    #[arg(long)]
+    pub compress: bool,
+    /// Encrypt the message with a key derived from this passphrase
+    #[cfg(feature = "encryption")]
+    #[arg(long)]
+    pub passphrase: Option<String>,
+}
+
+/// Print the message hidden in a PNG file.
+#[derive(Debug, Args)]
+pub struct DecodeArgs {
+    /// PNG file to read, or - for stdin
+    pub file_path: PathBuf,
+    /// Type of the chunk holding the message
+    #[arg(value_parser = parse_chunk_type)]
+    pub chunk_type: ChunkType,
+    /// Print every matching message instead of only the first
+    #[arg(long)]
+    pub all: bool,
+    /// Write the raw bytes of the message to this file, or - for stdout, instead of
+    /// printing it as text
+    #[arg(long, value_name = "PATH", conflicts_with = "all")]
+    pub output_file: Option<PathBuf>,
+    /// Match the chunk type without regard to case, e.g. rust finds ruSt
+    #[arg(long)]
+    pub ignore_case: bool,
+    /// Passphrase for decrypting an encrypted message
+    #[cfg(feature = "encryption")]
+    #[arg(long)]
+    pub passphrase: Option<String>,
+}
+
+/// Remove hidden messages from a PNG file.
+#[derive(Debug, Args)]
+pub struct RemoveArgs {
+    /// PNG file to modify, or - to read stdin and write stdout
+    pub file_path: PathBuf,
+    /// Types of the chunks to remove; the first chunk of each type is removed
+    #[arg(required = true, value_parser = parse_chunk_type)]
+    pub chunk_types: Vec<ChunkType>,
+    /// Match the chunk types without regard to case, e.g. rust finds ruSt
+    #[arg(long)]
+    pub ignore_case: bool,
+}
+
+/// List every chunk in a PNG file.
+#[derive(Debug, Args)]
+pub struct PrintArgs {
+    /// PNG file to read, or - for stdin
+    pub file_path: PathBuf,
+    /// Print exact byte counts instead of sizes like 1.2 MiB
+    #[arg(long)]
+    pub bytes: bool,
+    /// Only list chunks of this type; repeat for several types. Lists all by default
+    #[arg(long = "type", value_name = "TYPE", value_parser = parse_chunk_type)]
+    pub types: Vec<ChunkType>,
+}
+
+/// Remove every ancillary chunk from a PNG file.
+#[derive(Debug, Args)]
+pub struct StripArgs {
+    /// PNG file to modify, or - to read stdin and write stdout
+    pub file_path: PathBuf,
+}
+
+/// Describe every chunk in a PNG file, including its CRC and property flags.
+#[derive(Debug, Args)]
+pub struct MetaArgs {
+    /// PNG file to read, or - for stdin
+    pub file_path: PathBuf,
+    /// Print the chunk descriptors as a JSON array
+    #[arg(long)]
+    pub json: bool,
+    /// Print exact byte counts instead of sizes like 1.2 MiB; JSON always has them
+    #[arg(long)]
+    pub bytes: bool,
+}
+
+/// Count how many times each chunk type appears in a PNG file.
+#[derive(Debug, Args)]
+pub struct CountArgs {
+    /// PNG file to read, or - for stdin
+    pub file_path: PathBuf,
+}
+
+/// Check a PNG file's signature, CRCs and chunk order.
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// PNG file to read, or - for stdin
+    pub file_path: PathBuf,
+}
+
+/// Search every chunk's data for a string.
+#[derive(Debug, Args)]
+pub struct FindArgs {
+    /// PNG file to read, or - for stdin
+    pub file_path: PathBuf,
+    /// Text to search for, matched against the raw UTF-8 bytes
+    pub needle: String,
+}
+
+/// Compare the chunks of two PNG files.
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Original PNG file, or - for stdin
+    pub original: PathBuf,
+    /// Edited PNG file to compare against the original
+    pub edited: PathBuf,
+}
+
+/// Write the raw data of a chunk to a file.
+#[derive(Debug, Args)]
+pub struct ExtractArgs {
+    /// PNG file to read, or - for stdin
+    pub file_path: PathBuf,
+    /// Type of the chunk to extract
+    #[arg(value_parser = parse_chunk_type)]
+    pub chunk_type: ChunkType,
+    /// Where to write the chunk data, or - for stdout
+    pub output_file: PathBuf,
+}
+
+/// Change the type of every chunk of one type, keeping the data.
+#[derive(Debug, Args)]
+pub struct RenameArgs {
+    /// PNG file to modify, or - to read stdin and write stdout
+    pub file_path: PathBuf,
+    /// Type of the chunks to rename
+    #[arg(value_parser = parse_chunk_type)]
+    pub from: ChunkType,
+    /// New type for those chunks
+    #[arg(value_parser = parse_chunk_type)]
+    pub to: ChunkType,
+}
+
+/// Fix every chunk CRC that doesn't match the chunk's type and data.
+#[derive(Debug, Args)]
+pub struct RepairArgs {
+    /// PNG file to modify, or - to read stdin and write stdout
+    pub file_path: PathBuf,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_debug_assert() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_encode() {
+        let cli = Cli::try_parse_from(["pngme", "encode", "dice.png", "ruSt", "secret"]).unwrap();
+        match cli.command {
+            PngMeArgs::Encode(args) => {
+                assert_eq!(args.file_path, PathBuf::from("dice.png"));
+                assert_eq!(args.chunk_type.to_string(), "ruSt");
+                assert_eq!(args.messages, ["secret"]);
+                assert_eq!(args.message_file, None);
+                assert_eq!(args.output_file, None);
+                assert_eq!(args.output_dir, None);
+                assert_eq!(args.index, None);
+            }
+            other => panic!("expected encode, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_encode_with_output() {
+        let cli =
+            Cli::try_parse_from(["pngme", "encode", "in.png", "ruSt", "msg", "-o", "out.png"])
+                .unwrap();
+        match cli.command {
+            PngMeArgs::Encode(args) => {
+                assert_eq!(args.messages, ["msg"]);
+                assert_eq!(args.output_file, Some(PathBuf::from("out.png")));
+            }
+            other => panic!("expected encode, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_encode_with_output_dir() {
+        let cli = Cli::try_parse_from([
+            "pngme",
+            "encode",
+            "images",
+            "ruSt",
+            "msg",
+            "--output-dir",
+            "out",
+        ])
+        .unwrap();
+        match cli.command {
+            PngMeArgs::Encode(args) => assert_eq!(args.output_dir, Some(PathBuf::from("out"))),
+            other => panic!("expected encode, got {:?}", other),
+        }
+        assert!(Cli::try_parse_from([
+            "pngme",
+            "encode",
+            "images",
+            "ruSt",
+            "msg",
+            "--output-dir",
+            "out",
+            "-o",
+            "out.png",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_encode_with_index() {
+        let cli = Cli::try_parse_from(["pngme", "encode", "in.png", "ruSt", "msg", "--index", "1"])
+            .unwrap();
+        match cli.command {
+            PngMeArgs::Encode(args) => assert_eq!(args.index, Some(1)),
+            other => panic!("expected encode, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_encode_with_message_file() {
+        let cli = Cli::try_parse_from([
+            "pngme",
+            "encode",
+            "in.png",
+            "ruSt",
+            "--message-file",
+            "blob.bin",
+        ])
+        .unwrap();
+        match cli.command {
+            PngMeArgs::Encode(args) => {
+                assert!(args.messages.is_empty());
+                assert_eq!(args.message_file, Some(PathBuf::from("blob.bin")));
+            }
+            other => panic!("expected encode, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_encode_multiple_messages() {
+        let cli =
+            Cli::try_parse_from(["pngme", "encode", "in.png", "ruSt", "a", "b", "c"]).unwrap();
+        match cli.command {
+            PngMeArgs::Encode(args) => {
+                assert_eq!(args.messages, ["a", "b", "c"]);
+                assert_eq!(args.output_file, None);
+            }
+            other => panic!("expected encode, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_encode_requires_one_message_source() {
+        assert!(Cli::try_parse_from(["pngme", "encode", "in.png", "ruSt"]).is_err());
+        assert!(Cli::try_parse_from([
+            "pngme",
+            "encode",
+            "in.png",
+            "ruSt",
+            "msg",
+            "--message-file",
+            "blob.bin",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_remove_multiple_types() {
+        let cli = Cli::try_parse_from(["pngme", "remove", "in.png", "ruSt", "teXt"]).unwrap();
+        match cli.command {
+            PngMeArgs::Remove(args) => {
+                let types: Vec<String> = args.chunk_types.iter().map(|t| t.to_string()).collect();
+                assert_eq!(types, ["ruSt", "teXt"]);
+            }
+            other => panic!("expected remove, got {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["pngme", "remove", "in.png"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_verbosity() {
+        let cli = Cli::try_parse_from(["pngme", "strip", "in.png"]).unwrap();
+        assert_eq!(cli.verbosity(), Verbosity::Normal);
+        let cli = Cli::try_parse_from(["pngme", "strip", "in.png", "--quiet"]).unwrap();
+        assert_eq!(cli.verbosity(), Verbosity::Quiet);
+        let cli = Cli::try_parse_from(["pngme", "-v", "strip", "in.png"]).unwrap();
+        assert_eq!(cli.verbosity(), Verbosity::Verbose);
+        assert!(Cli::try_parse_from(["pngme", "-q", "-v", "strip", "in.png"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_hex_chunk_type() {
+        let ascii = Cli::try_parse_from(["pngme", "decode", "dice.png", "ruSt"]).unwrap();
+        let hex = Cli::try_parse_from(["pngme", "decode", "dice.png", "0x72755374"]).unwrap();
+        match (ascii.command, hex.command) {
+            (PngMeArgs::Decode(ascii), PngMeArgs::Decode(hex)) => {
+                assert_eq!(ascii.chunk_type, hex.chunk_type);
+                assert_eq!(hex.chunk_type.to_string(), "ruSt");
+            }
+            other => panic!("expected decode, got {:?}", other),
+        }
+        assert_eq!(parse_chunk_type("0X49454e44").unwrap().to_string(), "IEND");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_hex_chunk_type() {
+        assert_eq!(
+            parse_chunk_type("0x7275537"),
+            Err(ChunkTypeError::InvalidHex("0x7275537".to_string()))
+        );
+        assert_eq!(
+            parse_chunk_type("0x7275537g"),
+            Err(ChunkTypeError::InvalidHex("0x7275537g".to_string()))
+        );
+        // Well-formed hex still has to decode to ASCII letters.
+        assert_eq!(
+            parse_chunk_type("0x72753174"),
+            Err(ChunkTypeError::NonAsciiByte {
+                index: 2,
+                byte: b'1'
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_chunk_type() {
+        let err = Cli::try_parse_from(["pngme", "decode", "dice.png", "ru1t"]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Bytes must represent valid ASCII alphabetic characters"));
+    }
+}
@@ -0,0 +1,813 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+
+use crate::chunk_type::{ChunkType, ChunkTypeError};
+use crate::crc::Crc32;
+
+/// A single PNG chunk: a type, its data, and the CRC over both.
+///
+/// With the `serde` feature a chunk serializes as its `chunk_type`, `length`, `crc`
+/// and `data` (as a byte array). Deserializing recomputes the CRC and rejects a
+/// mismatch; `length` is informational and ignored.
+///
+/// Chunks compare equal when their type and data are equal. The stored CRC is left out
+/// of the comparison: it is normally verified or computed on construction, but a chunk
+/// read without CRC checks can carry a stale one.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "ChunkRepr", try_from = "ChunkRepr")
+)]
+pub struct Chunk {
+    chunk_type: ChunkType,
+    data: Vec<u8>,
+    crc: u32,
+}
+
+impl Chunk {
+    /// Largest data length the PNG spec allows, 2^31 - 1 bytes.
+    pub const MAX_LENGTH: u32 = 0x7fff_ffff;
+
+    /// Default cap on the data length [`Chunk::from_reader`] will accept, 64 MiB.
+    pub const DEFAULT_READ_LIMIT: u32 = 64 * 1024 * 1024;
+
+    /// Size in bytes of the big-endian length field that starts a serialized chunk.
+    pub const LENGTH_SIZE: usize = 4;
+
+    /// Size in bytes of the chunk type field, which follows the length.
+    pub const TYPE_SIZE: usize = ChunkType::LENGTH;
+
+    /// Size in bytes of the big-endian CRC that ends a serialized chunk.
+    pub const CRC_SIZE: usize = 4;
+
+    /// Bytes a serialized chunk takes on top of its data: length, type and CRC.
+    pub const METADATA_SIZE: usize = Self::LENGTH_SIZE + Self::TYPE_SIZE + Self::CRC_SIZE;
+
+    /// Creates a chunk, computing its CRC from the type and data.
+    pub fn new(chunk_type: ChunkType, data: Vec<u8>) -> Chunk {
+        let crc = Self::compute_crc(&chunk_type, &data);
+        Chunk {
+            chunk_type,
+            data,
+            crc,
+        }
+    }
+
+    /// Creates a chunk holding `text` as UTF-8, parsing and validating `chunk_type` first.
+    pub fn new_text(chunk_type: &str, text: &str) -> crate::Result<Chunk> {
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        Ok(Chunk::new(chunk_type, text.as_bytes().to_vec()))
+    }
+
+    /// Length of the chunk data in bytes, not counting the type or CRC.
+    pub fn length(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    pub fn chunk_type(&self) -> &ChunkType {
+        &self.chunk_type
+    }
+
+    /// The chunk type code as a string slice borrowed from the chunk, for comparisons
+    /// in hot loops where `chunk_type().to_string()` would allocate.
+    pub fn chunk_type_str(&self) -> &str {
+        self.chunk_type.as_str()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    /// True if the stored CRC matches the one computed over the type and data. Cheaper
+    /// than verifying a whole [`Png`](crate::Png) when only this chunk matters.
+    pub fn crc_is_valid(&self) -> bool {
+        self.crc == Self::compute_crc(&self.chunk_type, &self.data)
+    }
+
+    /// Replaces the stored CRC with the one computed over the type and data.
+    pub(crate) fn recompute_crc(&mut self) {
+        self.crc = Self::compute_crc(&self.chunk_type, &self.data);
+    }
+
+    /// Changes the chunk type, keeping the data and recomputing the CRC to match.
+    pub fn set_chunk_type(&mut self, chunk_type: ChunkType) {
+        self.crc = Self::compute_crc(&chunk_type, &self.data);
+        self.chunk_type = chunk_type;
+    }
+
+    /// Replaces the chunk data, recomputing the CRC to match.
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        self.crc = Self::compute_crc(&self.chunk_type, &data);
+        self.data = data;
+    }
+
+    /// Reads one chunk from `reader`: the length, type, data and CRC in turn.
+    ///
+    /// The CRC is verified once the chunk has been read. Running out of input part way
+    /// through is reported as [`ChunkError::UnexpectedEof`]. Chunks declaring more than
+    /// [`Chunk::DEFAULT_READ_LIMIT`] bytes of data are rejected.
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Chunk, ChunkError> {
+        Self::from_reader_with_limit(reader, Self::DEFAULT_READ_LIMIT)
+    }
+
+    /// Like [`Chunk::from_reader`], but rejects chunks declaring more than `limit` bytes
+    /// of data with [`ChunkError::ChunkTooLarge`].
+    ///
+    /// The data buffer grows as bytes arrive rather than being sized from the declared
+    /// length up front, so a lying length field can't force a large allocation.
+    pub fn from_reader_with_limit<R: Read>(
+        reader: &mut R,
+        limit: u32,
+    ) -> Result<Chunk, ChunkError> {
+        Self::read_with_options(reader, limit, true)
+    }
+
+    /// [`Chunk::from_reader_with_limit`], optionally keeping the stored CRC as read
+    /// instead of checking it, so a chunk with a stale CRC can be loaded and repaired.
+    pub(crate) fn read_with_options<R: Read>(
+        reader: &mut R,
+        limit: u32,
+        verify_crc: bool,
+    ) -> Result<Chunk, ChunkError> {
+        // The length, type and CRC fields are all four bytes, so one buffer serves for each.
+        let mut word = [0; Self::LENGTH_SIZE];
+        read_exact(reader, &mut word)?;
+        let length = check_length(u32::from_be_bytes(word))?;
+        if length > limit {
+            return Err(ChunkError::ChunkTooLarge {
+                declared: length,
+                limit,
+            });
+        }
+
+        read_exact(reader, &mut word)?;
+        let chunk_type = ChunkType::try_from(word)?;
+
+        let mut data = Vec::new();
+        reader.take(length as u64).read_to_end(&mut data)?;
+        if data.len() < length as usize {
+            return Err(ChunkError::UnexpectedEof);
+        }
+
+        read_exact(reader, &mut word)?;
+        let crc = u32::from_be_bytes(word);
+        if verify_crc {
+            Self::with_stored_crc(chunk_type, data, crc)
+        } else {
+            Ok(Chunk {
+                chunk_type,
+                data,
+                crc,
+            })
+        }
+    }
+
+    /// Builds a chunk and checks `crc` against the one computed from the type and data.
+    fn with_stored_crc(
+        chunk_type: ChunkType,
+        data: Vec<u8>,
+        crc: u32,
+    ) -> Result<Chunk, ChunkError> {
+        let chunk = Chunk::new(chunk_type, data);
+        if chunk.crc != crc {
+            return Err(ChunkError::CrcMismatch {
+                expected: chunk.crc,
+                actual: crc,
+            });
+        }
+        Ok(chunk)
+    }
+
+    /// Returns the chunk data as a string, failing if it isn't valid UTF-8.
+    pub fn data_as_string(&self) -> Result<String, ChunkError> {
+        String::from_utf8(self.data.clone()).map_err(|_| ChunkError::InvalidUtf8)
+    }
+
+    /// Serializes the chunk as it appears in a PNG file: the big-endian data length,
+    /// the chunk type, the data, then the big-endian CRC.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::METADATA_SIZE + self.data.len());
+        bytes.extend_from_slice(&self.length().to_be_bytes());
+        bytes.extend_from_slice(&self.chunk_type.bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes.extend_from_slice(&self.crc.to_be_bytes());
+        bytes
+    }
+
+    fn compute_crc(chunk_type: &ChunkType, data: &[u8]) -> u32 {
+        Crc32::new()
+            .update(&chunk_type.bytes())
+            .update(data)
+            .finalize()
+    }
+}
+
+/// Serialized form of a [`Chunk`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ChunkRepr {
+    chunk_type: ChunkType,
+    #[serde(default)]
+    length: u32,
+    crc: u32,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Chunk> for ChunkRepr {
+    fn from(chunk: Chunk) -> Self {
+        ChunkRepr {
+            chunk_type: chunk.chunk_type,
+            length: chunk.length(),
+            crc: chunk.crc,
+            data: chunk.data,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ChunkRepr> for Chunk {
+    type Error = ChunkError;
+
+    fn try_from(repr: ChunkRepr) -> Result<Self, Self::Error> {
+        Chunk::with_stored_crc(repr.chunk_type, repr.data, repr.crc)
+    }
+}
+
+/// Single-line summary of the chunk, with a short preview of the data when it is text.
+impl fmt::Display for Chunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Chunk {{ type: {}, length: {}, crc: {}",
+            self.chunk_type,
+            self.length(),
+            self.crc
+        )?;
+        if let Ok(text) = std::str::from_utf8(&self.data) {
+            if !text.is_empty() {
+                let preview: String = text.chars().take(PREVIEW_CHARS).collect();
+                let ellipsis = if preview.len() < text.len() {
+                    "..."
+                } else {
+                    ""
+                };
+                // Debug formatting escapes newlines so the summary stays on one line.
+                write!(f, ", data: {:?}{}", preview, ellipsis)?;
+            }
+        }
+        write!(f, " }}")
+    }
+}
+
+/// Maximum number of characters of text data shown by `Display`.
+const PREVIEW_CHARS: usize = 32;
+
+/// Errors produced when parsing a [`Chunk`].
+#[derive(Debug)]
+pub enum ChunkError {
+    /// The input ended before the declared length, type, data and CRC were all read.
+    TooShort,
+    /// A reader hit end of file part way through a chunk.
+    UnexpectedEof,
+    /// The declared length has the high bit set, exceeding [`Chunk::MAX_LENGTH`].
+    LengthTooLarge(u32),
+    /// The declared length is above the caller's read limit.
+    ChunkTooLarge { declared: u32, limit: u32 },
+    /// The chunk type bytes are invalid.
+    InvalidChunkType(ChunkTypeError),
+    /// The stored CRC does not match the one computed over the type and data.
+    /// `expected` is the computed value, `actual` the one read from the input.
+    CrcMismatch { expected: u32, actual: u32 },
+    /// The chunk data is not valid UTF-8.
+    InvalidUtf8,
+    /// Reading from the underlying reader failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkError::TooShort => write!(f, "Chunk is shorter than its declared length"),
+            ChunkError::UnexpectedEof => write!(f, "Unexpected end of input inside a chunk"),
+            ChunkError::LengthTooLarge(length) => write!(
+                f,
+                "Chunk length {} exceeds the maximum of {}",
+                length,
+                Chunk::MAX_LENGTH
+            ),
+            ChunkError::ChunkTooLarge { declared, limit } => write!(
+                f,
+                "Chunk length {} exceeds the read limit of {} bytes",
+                declared, limit
+            ),
+            ChunkError::InvalidChunkType(e) => write!(f, "Invalid chunk type: {}", e),
+            ChunkError::CrcMismatch { expected, actual } => write!(
+                f,
+                "CRC mismatch: expected {:#010x}, found {:#010x}",
+                expected, actual
+            ),
+            ChunkError::InvalidUtf8 => write!(f, "Chunk data is not valid UTF-8"),
+            ChunkError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ChunkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChunkError::InvalidChunkType(e) => Some(e),
+            ChunkError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ChunkTypeError> for ChunkError {
+    fn from(e: ChunkTypeError) -> Self {
+        ChunkError::InvalidChunkType(e)
+    }
+}
+
+impl From<io::Error> for ChunkError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => ChunkError::UnexpectedEof,
+            _ => ChunkError::Io(e),
+        }
+    }
+}
+
+/// Compares type and data only, not the stored CRC.
+impl PartialEq for Chunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.chunk_type == other.chunk_type && self.data == other.data
+    }
+}
+
+impl Eq for Chunk {}
+
+/// Parses a chunk from the start of `bytes`. Any bytes after the CRC are ignored.
+impl TryFrom<&[u8]> for Chunk {
+    type Error = ChunkError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (length, rest) = split_u32(bytes)?;
+        let length = check_length(length)? as usize;
+        if rest.len() < Chunk::TYPE_SIZE {
+            return Err(ChunkError::TooShort);
+        }
+        let (chunk_type, rest) = rest.split_at(Chunk::TYPE_SIZE);
+        let chunk_type = ChunkType::try_from(chunk_type)?;
+        if rest.len() < length {
+            return Err(ChunkError::TooShort);
+        }
+        let (data, rest) = rest.split_at(length);
+        let (crc, _) = split_u32(rest)?;
+
+        Chunk::with_stored_crc(chunk_type, data.to_vec(), crc)
+    }
+}
+
+/// Rejects declared lengths above [`Chunk::MAX_LENGTH`] before anything is allocated.
+fn check_length(length: u32) -> Result<u32, ChunkError> {
+    if length > Chunk::MAX_LENGTH {
+        return Err(ChunkError::LengthTooLarge(length));
+    }
+    Ok(length)
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), ChunkError> {
+    Ok(reader.read_exact(buf)?)
+}
+
+/// Splits a big-endian `u32`, a length or CRC field, off the front of `bytes`.
+fn split_u32(bytes: &[u8]) -> Result<(u32, &[u8]), ChunkError> {
+    const _: () = assert!(Chunk::LENGTH_SIZE == Chunk::CRC_SIZE);
+    if bytes.len() < Chunk::LENGTH_SIZE {
+        return Err(ChunkError::TooShort);
+    }
+    let (head, rest) = bytes.split_at(Chunk::LENGTH_SIZE);
+    let value = u32::from_be_bytes([head[0], head[1], head[2], head[3]]);
+    Ok((value, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crc::crc32;
+    use proptest::prelude::*;
+    use std::str::FromStr;
+
+    fn chunk_bytes(data_length: u32, chunk_type: &[u8], message: &[u8], crc: u32) -> Vec<u8> {
+        data_length
+            .to_be_bytes()
+            .iter()
+            .chain(chunk_type)
+            .chain(message)
+            .chain(crc.to_be_bytes().iter())
+            .copied()
+            .collect()
+    }
+
+    fn testing_chunk() -> Chunk {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let data = "This is where your secret message will be!"
+            .as_bytes()
+            .to_vec();
+        Chunk::new(chunk_type, data)
+    }
+
+    #[test]
+    fn test_new_chunk() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let data = "This is where your secret message will be!"
+            .as_bytes()
+            .to_vec();
+        let chunk = Chunk::new(chunk_type, data);
+        assert_eq!(chunk.length(), 42);
+        assert_eq!(chunk.crc(), 2882656334);
+    }
+
+    #[test]
+    fn test_chunk_length() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.length(), 42);
+    }
+
+    #[test]
+    fn test_chunk_type() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.chunk_type().to_string(), String::from("RuSt"));
+    }
+
+    #[test]
+    fn test_chunk_type_str() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.chunk_type_str(), "RuSt");
+        assert_eq!(chunk.chunk_type_str(), chunk.chunk_type().to_string());
+    }
+
+    #[test]
+    fn test_chunk_data() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.data(), b"This is where your secret message will be!");
+    }
+
+    #[test]
+    fn test_chunk_string() {
+        let chunk = testing_chunk();
+        let chunk_string = chunk.data_as_string().unwrap();
+        let expected_chunk_string = String::from("This is where your secret message will be!");
+        assert_eq!(chunk_string, expected_chunk_string);
+    }
+
+    #[test]
+    fn test_chunk_string_invalid_utf8() {
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), vec![0xff, 0xfe]);
+        assert!(matches!(
+            chunk.data_as_string(),
+            Err(ChunkError::InvalidUtf8)
+        ));
+    }
+
+    #[test]
+    fn test_chunk_crc() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.crc(), 2882656334);
+    }
+
+    #[test]
+    fn test_empty_chunk() {
+        let chunk = Chunk::new(ChunkType::IEND, Vec::new());
+        assert_eq!(chunk.length(), 0);
+        assert_eq!(chunk.crc(), 0xAE42_6082);
+    }
+
+    #[test]
+    fn test_chunk_as_bytes() {
+        let chunk = testing_chunk();
+        let bytes = chunk.as_bytes();
+
+        assert_eq!(bytes.len(), Chunk::METADATA_SIZE + 42);
+        assert_eq!(&bytes[..4], &42u32.to_be_bytes());
+        assert_eq!(&bytes[4..8], b"RuSt");
+        assert_eq!(&bytes[8..50], chunk.data());
+        assert_eq!(&bytes[50..], &2882656334u32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_empty_chunk_as_bytes() {
+        let bytes = Chunk::new(ChunkType::IEND, Vec::new()).as_bytes();
+        assert_eq!(
+            bytes,
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+        );
+    }
+
+    #[test]
+    fn test_valid_chunk_from_bytes() {
+        let message = b"This is where your secret message will be!";
+        let chunk_data = chunk_bytes(42, b"RuSt", message, 2882656334);
+
+        let chunk = Chunk::try_from(chunk_data.as_ref()).unwrap();
+
+        assert_eq!(chunk.length(), 42);
+        assert_eq!(chunk.chunk_type().to_string(), String::from("RuSt"));
+        assert_eq!(chunk.data(), message);
+        assert_eq!(chunk.crc(), 2882656334);
+    }
+
+    #[test]
+    fn test_invalid_chunk_from_bytes() {
+        let message = b"This is where your secret message will be!";
+        let chunk_data = chunk_bytes(42, b"RuSt", message, 2882656333);
+
+        let chunk = Chunk::try_from(chunk_data.as_ref());
+
+        assert!(matches!(
+            chunk,
+            Err(ChunkError::CrcMismatch {
+                expected: 2882656334,
+                actual: 2882656333
+            })
+        ));
+    }
+
+    #[test]
+    fn test_chunk_too_short() {
+        let message = b"This is where your secret message will be!";
+        let chunk_data = chunk_bytes(43, b"RuSt", message, 2882656334);
+        assert!(matches!(
+            Chunk::try_from(chunk_data.as_ref()),
+            Err(ChunkError::TooShort)
+        ));
+
+        for len in 0..12 {
+            let bytes = &Chunk::new(ChunkType::IEND, Vec::new()).as_bytes()[..len];
+            assert!(matches!(Chunk::try_from(bytes), Err(ChunkError::TooShort)));
+        }
+    }
+
+    #[test]
+    fn test_chunk_invalid_type() {
+        let chunk_data = chunk_bytes(0, b"Ru1t", &[], 0);
+        assert!(matches!(
+            Chunk::try_from(chunk_data.as_ref()),
+            Err(ChunkError::InvalidChunkType(_))
+        ));
+    }
+
+    #[test]
+    fn test_chunk_round_trip() {
+        let chunk = testing_chunk();
+        let parsed = Chunk::try_from(&chunk.as_bytes()[..]).unwrap();
+
+        assert_eq!(parsed.chunk_type(), chunk.chunk_type());
+        assert_eq!(parsed.data(), chunk.data());
+        assert_eq!(parsed.crc(), chunk.crc());
+    }
+
+    #[test]
+    fn test_chunk_from_reader() {
+        let chunk = testing_chunk();
+        let mut bytes = chunk.as_bytes();
+        bytes.extend_from_slice(b"next");
+        let mut reader = io::Cursor::new(bytes);
+
+        let parsed = Chunk::from_reader(&mut reader).unwrap();
+        assert_eq!(parsed.chunk_type(), chunk.chunk_type());
+        assert_eq!(parsed.data(), chunk.data());
+        assert_eq!(parsed.crc(), chunk.crc());
+        // The reader is left positioned just after the chunk.
+        assert_eq!(reader.position(), 54);
+    }
+
+    #[test]
+    fn test_chunk_from_reader_unexpected_eof() {
+        let bytes = testing_chunk().as_bytes();
+        for len in [0, 3, 6, 20, bytes.len() - 1] {
+            assert!(matches!(
+                Chunk::from_reader(&mut &bytes[..len]),
+                Err(ChunkError::UnexpectedEof)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_chunk_from_reader_crc_mismatch() {
+        let message = b"This is where your secret message will be!";
+        let chunk_data = chunk_bytes(42, b"RuSt", message, 2882656333);
+        assert!(matches!(
+            Chunk::from_reader(&mut chunk_data.as_slice()),
+            Err(ChunkError::CrcMismatch { .. })
+        ));
+    }
+
+    #[test]
+    pub fn test_chunk_trait_impls() {
+        let message = b"This is where your secret message will be!";
+        let chunk_data = chunk_bytes(42, b"RuSt", message, 2882656334);
+
+        let chunk: Chunk = TryFrom::try_from(chunk_data.as_ref()).unwrap();
+
+        let _chunk_string = format!("{}", chunk);
+    }
+
+    #[test]
+    fn test_chunk_display() {
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hi\nthere".to_vec());
+        assert_eq!(
+            chunk.to_string(),
+            format!(
+                "Chunk {{ type: ruSt, length: 8, crc: {}, data: \"hi\\nthere\" }}",
+                chunk.crc()
+            )
+        );
+    }
+
+    #[test]
+    fn test_chunk_display_truncates_preview() {
+        let chunk = testing_chunk();
+        assert_eq!(
+            chunk.to_string(),
+            "Chunk { type: RuSt, length: 42, crc: 2882656334, data: \"This is where your secret messag\"... }"
+        );
+    }
+
+    #[test]
+    fn test_chunk_display_without_preview() {
+        let chunk = Chunk::new(ChunkType::IEND, Vec::new());
+        assert_eq!(
+            chunk.to_string(),
+            "Chunk { type: IEND, length: 0, crc: 2923585666 }"
+        );
+
+        let chunk = Chunk::new(ChunkType::IDAT, vec![0xff, 0x00]);
+        assert!(!chunk.to_string().contains("data"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_chunk_serde_round_trip() {
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hi".to_vec());
+        let json = serde_json::to_string(&chunk).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                "{{\"chunk_type\":\"ruSt\",\"length\":2,\"crc\":{},\"data\":[104,105]}}",
+                chunk.crc()
+            )
+        );
+
+        let parsed: Chunk = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.as_bytes(), chunk.as_bytes());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_chunk_serde_rejects_bad_crc() {
+        let json = r#"{"chunk_type":"ruSt","length":2,"crc":1,"data":[104,105]}"#;
+        assert!(serde_json::from_str::<Chunk>(json).is_err());
+    }
+
+    #[test]
+    fn test_chunk_length_too_large() {
+        let chunk_data = chunk_bytes(0x8000_0000, b"RuSt", &[], 0);
+        assert!(matches!(
+            Chunk::try_from(chunk_data.as_ref()),
+            Err(ChunkError::LengthTooLarge(0x8000_0000))
+        ));
+        assert!(matches!(
+            Chunk::from_reader(&mut chunk_data.as_slice()),
+            Err(ChunkError::LengthTooLarge(0x8000_0000))
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn test_chunk_bytes_round_trip(
+            chunk_type in "[a-zA-Z]{4}",
+            data in prop_oneof![
+                Just(Vec::new()),
+                prop::collection::vec(any::<u8>(), 0..1024),
+                prop::collection::vec(any::<u8>(), 65_536),
+            ],
+        ) {
+            let chunk = Chunk::new(ChunkType::from_str(&chunk_type).unwrap(), data);
+            let parsed = Chunk::try_from(chunk.as_bytes().as_ref()).unwrap();
+            prop_assert_eq!(parsed, chunk);
+        }
+
+        // The largest legal length can't be allocated in a test, so check the boundary
+        // from the other side: every declared length past it is refused before reading.
+        #[test]
+        fn test_chunk_length_past_max_rejected(length in Chunk::MAX_LENGTH + 1..=u32::MAX) {
+            let chunk_data = chunk_bytes(length, b"RuSt", &[], 0);
+            prop_assert!(matches!(
+                Chunk::try_from(chunk_data.as_ref()),
+                Err(ChunkError::LengthTooLarge(l)) if l == length
+            ));
+        }
+    }
+
+    #[test]
+    fn test_chunk_from_reader_with_limit() {
+        let bytes = testing_chunk().as_bytes();
+        assert!(Chunk::from_reader_with_limit(&mut bytes.as_slice(), 42).is_ok());
+        assert!(matches!(
+            Chunk::from_reader_with_limit(&mut bytes.as_slice(), 41),
+            Err(ChunkError::ChunkTooLarge {
+                declared: 42,
+                limit: 41
+            })
+        ));
+    }
+
+    #[test]
+    fn test_chunk_from_reader_default_limit() {
+        let chunk_data = chunk_bytes(Chunk::DEFAULT_READ_LIMIT + 1, b"IDAT", &[], 0);
+        assert!(matches!(
+            Chunk::from_reader(&mut chunk_data.as_slice()),
+            Err(ChunkError::ChunkTooLarge { .. })
+        ));
+
+        // Under the limit, a lying length just runs out of input.
+        let chunk_data = chunk_bytes(Chunk::DEFAULT_READ_LIMIT, b"IDAT", &[], 0);
+        assert!(matches!(
+            Chunk::from_reader(&mut chunk_data.as_slice()),
+            Err(ChunkError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn test_new_text() {
+        let chunk = Chunk::new_text("RuSt", "This is where your secret message will be!").unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "RuSt");
+        assert_eq!(
+            chunk.data_as_string().unwrap(),
+            "This is where your secret message will be!"
+        );
+        assert_eq!(chunk.crc(), 2882656334);
+
+        assert!(matches!(
+            Chunk::new_text("Ru1t", "message"),
+            Err(crate::Error::ChunkType(_))
+        ));
+    }
+
+    #[test]
+    fn test_chunk_equality() {
+        let chunk = Chunk::new_text("RuSt", "secret").unwrap();
+        assert_eq!(chunk, Chunk::new_text("RuSt", "secret").unwrap());
+        assert_eq!(chunk, Chunk::try_from(chunk.as_bytes().as_ref()).unwrap());
+        assert_ne!(chunk, Chunk::new_text("RuSt", "secreT").unwrap());
+        assert_ne!(chunk, Chunk::new_text("RuSx", "secret").unwrap());
+
+        let mut bytes = chunk.as_bytes();
+        *bytes.last_mut().unwrap() ^= 0xff;
+        let stale = Chunk::read_with_options(&mut &bytes[..], Chunk::MAX_LENGTH, false).unwrap();
+        assert!(!stale.crc_is_valid());
+        assert_eq!(chunk, stale);
+    }
+
+    #[test]
+    fn test_set_data() {
+        let mut chunk = Chunk::new_text("RuSt", "before").unwrap();
+        chunk.set_data(b"after".to_vec());
+        assert_eq!(chunk.data(), b"after");
+        assert_eq!(chunk, Chunk::new_text("RuSt", "after").unwrap());
+        assert_eq!(chunk.crc(), crc32(b"RuStafter"));
+    }
+
+    #[test]
+    fn test_crc_is_valid() {
+        let mut chunk = testing_chunk();
+        assert!(chunk.crc_is_valid());
+
+        chunk.data[0] ^= 0x01;
+        assert!(!chunk.crc_is_valid());
+        chunk.data[0] ^= 0x01;
+        chunk.crc ^= 0x01;
+        assert!(!chunk.crc_is_valid());
+    }
+
+    #[test]
+    fn test_metadata_size() {
+        const _: () = assert!(
+            Chunk::METADATA_SIZE == Chunk::LENGTH_SIZE + Chunk::TYPE_SIZE + Chunk::CRC_SIZE
+        );
+        assert_eq!(Chunk::METADATA_SIZE, 12);
+        assert_eq!(
+            Chunk::new_text("RuSt", "").unwrap().as_bytes().len(),
+            Chunk::METADATA_SIZE
+        );
+    }
+}
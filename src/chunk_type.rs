@@ -0,0 +1,748 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// A four-byte PNG chunk type code, e.g. `IHDR` or `ruSt`.
+///
+/// The case of each byte carries meaning: bit 5 (the ASCII lowercase bit)
+/// of each byte is a property flag as described in the PNG specification.
+///
+/// Chunk types order lexicographically by their raw bytes, so uppercase letters
+/// sort before lowercase ones.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct ChunkType {
+    bytes: [u8; ChunkType::LENGTH],
+}
+
+/// The chunk types the PNG specification defines, with their descriptions.
+const DESCRIPTIONS: [(&[u8; 4], &str); 25] = [
+    (b"IHDR", "Image header"),
+    (b"PLTE", "Palette"),
+    (b"IDAT", "Image data"),
+    (b"IEND", "Image trailer"),
+    (b"acTL", "Animation control"),
+    (b"bKGD", "Background color"),
+    (b"cHRM", "Primary chromaticities and white point"),
+    (b"cICP", "Coding-independent code points"),
+    (b"cLLI", "Content light level information"),
+    (b"eXIf", "Exif data"),
+    (b"fcTL", "Frame control"),
+    (b"fdAT", "Frame data"),
+    (b"gAMA", "Image gamma"),
+    (b"hIST", "Image histogram"),
+    (b"iCCP", "Embedded ICC profile"),
+    (b"iTXt", "International textual data"),
+    (b"mDCV", "Mastering display color volume"),
+    (b"pHYs", "Physical pixel dimensions"),
+    (b"sBIT", "Significant bits"),
+    (b"sPLT", "Suggested palette"),
+    (b"sRGB", "Standard RGB color space"),
+    (b"tEXt", "Textual data"),
+    (b"tIME", "Image last-modification time"),
+    (b"tRNS", "Transparency"),
+    (b"zTXt", "Compressed textual data"),
+];
+
+/// All four property flags of a [`ChunkType`], as returned by [`ChunkType::properties`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChunkProperties {
+    pub critical: bool,
+    pub public: bool,
+    pub reserved_valid: bool,
+    pub safe_to_copy: bool,
+}
+
+impl ChunkType {
+    /// Length of a chunk type code in bytes.
+    pub const LENGTH: usize = 4;
+
+    /// Image header; must be the first chunk.
+    pub const IHDR: ChunkType = ChunkType::from_bytes_unchecked(*b"IHDR");
+    /// Palette table.
+    pub const PLTE: ChunkType = ChunkType::from_bytes_unchecked(*b"PLTE");
+    /// Image data.
+    pub const IDAT: ChunkType = ChunkType::from_bytes_unchecked(*b"IDAT");
+    /// Image trailer; must be the last chunk.
+    pub const IEND: ChunkType = ChunkType::from_bytes_unchecked(*b"IEND");
+    /// Textual data (`tEXt`).
+    pub const TEXT: ChunkType = ChunkType::from_bytes_unchecked(*b"tEXt");
+    /// Compressed textual data (`zTXt`).
+    pub const ZTXT: ChunkType = ChunkType::from_bytes_unchecked(*b"zTXt");
+    /// International textual data (`iTXt`).
+    pub const ITXT: ChunkType = ChunkType::from_bytes_unchecked(*b"iTXt");
+
+    /// Builds a chunk type without validating the bytes, for callers that already have.
+    ///
+    /// The caller must pass four ASCII letters, as `TryFrom<[u8; 4]>` would require.
+    /// Nothing unsafe happens otherwise, but the chunk type breaks the invariants the
+    /// rest of the crate relies on, e.g. that it displays as valid UTF-8. Debug builds
+    /// check the bytes and panic if they are invalid.
+    pub const fn from_bytes_unchecked(bytes: [u8; 4]) -> ChunkType {
+        let mut i = 0;
+        while i < bytes.len() {
+            debug_assert!(
+                bytes[i].is_ascii_alphabetic(),
+                "chunk type bytes must be ASCII letters"
+            );
+            i += 1;
+        }
+        ChunkType { bytes }
+    }
+
+    /// Returns the raw bytes of this chunk type.
+    pub fn bytes(&self) -> [u8; 4] {
+        self.bytes
+    }
+
+    /// The type code as a string slice, without allocating as `to_string` does.
+    pub fn as_str(&self) -> &str {
+        // Construction guarantees ASCII letters, so this is always valid UTF-8.
+        std::str::from_utf8(&self.bytes).expect("chunk type bytes are ASCII letters")
+    }
+
+    /// Returns true if every byte is an ASCII letter and the reserved bit is valid.
+    pub fn is_valid(&self) -> bool {
+        self.bytes.iter().all(|&b| Self::is_valid_byte(b)) && self.is_reserved_bit_valid()
+    }
+
+    /// Whether the property bit (bit 5, the ASCII lowercase bit) of byte `index` is set,
+    /// or `None` if `index` is not 0 to 3.
+    ///
+    /// The four bits are, in order, the ancillary, private, reserved and safe-to-copy
+    /// bits; the named predicates below are each one of these, read the right way round.
+    pub fn property_bit(&self, index: usize) -> Option<bool> {
+        self.bytes.get(index).map(|byte| byte & 0x20 != 0)
+    }
+
+    /// Critical chunks have an uppercase first byte.
+    pub fn is_critical(&self) -> bool {
+        self.bytes[0] & 0x20 == 0
+    }
+
+    /// Ancillary chunks have a lowercase first byte; the inverse of [`is_critical`](Self::is_critical).
+    pub fn is_ancillary(&self) -> bool {
+        !self.is_critical()
+    }
+
+    /// Public chunks have an uppercase second byte.
+    pub fn is_public(&self) -> bool {
+        self.bytes[1] & 0x20 == 0
+    }
+
+    /// Private chunks have a lowercase second byte; the inverse of [`is_public`](Self::is_public).
+    pub fn is_private(&self) -> bool {
+        !self.is_public()
+    }
+
+    /// The third byte must be uppercase in PNG files conforming to the current spec.
+    pub fn is_reserved_bit_valid(&self) -> bool {
+        self.bytes[2] & 0x20 == 0
+    }
+
+    /// Safe-to-copy chunks have a lowercase fourth byte.
+    pub fn is_safe_to_copy(&self) -> bool {
+        self.bytes[3] & 0x20 != 0
+    }
+
+    /// Returns every property flag at once.
+    pub fn properties(&self) -> ChunkProperties {
+        ChunkProperties {
+            critical: self.is_critical(),
+            public: self.is_public(),
+            reserved_valid: self.is_reserved_bit_valid(),
+            safe_to_copy: self.is_safe_to_copy(),
+        }
+    }
+
+    /// A short description of a chunk type the PNG specification defines, e.g.
+    /// `Image gamma` for `gAMA`, or `None` for any other type.
+    pub fn description(&self) -> Option<&'static str> {
+        DESCRIPTIONS
+            .iter()
+            .find(|(bytes, _)| **bytes == self.bytes)
+            .map(|&(_, description)| description)
+    }
+
+    /// Returns a copy of this chunk type with the first byte lowercased, marking it ancillary.
+    pub fn to_ancillary(&self) -> ChunkType {
+        let mut bytes = self.bytes;
+        bytes[0] |= 0x20;
+        ChunkType { bytes }
+    }
+
+    /// Returns a copy of this chunk type with the first byte uppercased, marking it critical.
+    pub fn to_critical(&self) -> ChunkType {
+        let mut bytes = self.bytes;
+        bytes[0] &= !0x20;
+        ChunkType { bytes }
+    }
+
+    /// Checks that `s` could be parsed as a chunk type, reporting exactly what is wrong.
+    ///
+    /// Surrounding ASCII whitespace, such as a pasted trailing newline, is ignored. A
+    /// string of the wrong length gives [`ChunkTypeError::InvalidLength`]; otherwise the
+    /// first non-letter gives [`ChunkTypeError::NonAsciiByte`] with its byte index (in the
+    /// trimmed string) and value, which is enough to point at the offending character.
+    pub fn validate_with_detail(s: &str) -> Result<(), ChunkTypeError> {
+        Self::check_bytes(s.trim_matches(|c: char| c.is_ascii_whitespace()).as_bytes())
+    }
+
+    fn check_bytes(bytes: &[u8]) -> Result<(), ChunkTypeError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(ChunkTypeError::InvalidLength(bytes.len()));
+        }
+        if std::str::from_utf8(bytes).is_err() {
+            return Err(ChunkTypeError::InvalidUtf8);
+        }
+        match bytes.iter().position(|&b| !Self::is_valid_byte(b)) {
+            Some(index) => Err(ChunkTypeError::NonAsciiByte {
+                index,
+                byte: bytes[index],
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Builds a chunk type from bytes already accepted by [`ChunkType::check_bytes`].
+    fn from_checked_slice(bytes: &[u8]) -> ChunkType {
+        let mut array = [0; Self::LENGTH];
+        array.copy_from_slice(bytes);
+        ChunkType { bytes: array }
+    }
+
+    fn is_valid_byte(byte: u8) -> bool {
+        byte.is_ascii_alphabetic()
+    }
+}
+
+/// Errors produced when building a [`ChunkType`] from bytes or a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkTypeError {
+    /// The input was not exactly four bytes long; carries the actual length.
+    InvalidLength(usize),
+    /// The byte at `index` is not an ASCII letter.
+    NonAsciiByte { index: usize, byte: u8 },
+    /// The input bytes could not be decoded as UTF-8, so have no text to show.
+    InvalidUtf8,
+    /// A `0x`-prefixed type was not exactly eight hexadecimal digits.
+    InvalidHex(String),
+}
+
+impl fmt::Display for ChunkTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkTypeError::InvalidLength(len) => {
+                write!(
+                    f,
+                    "Ensure string is {} bytes (got {} bytes)",
+                    ChunkType::LENGTH,
+                    len
+                )
+            }
+            ChunkTypeError::NonAsciiByte { index, byte } => write!(
+                f,
+                "Bytes must represent valid ASCII alphabetic characters (found {:#04x} at index {})",
+                byte, index
+            ),
+            ChunkTypeError::InvalidUtf8 => write!(f, "Chunk type must be valid UTF-8"),
+            ChunkTypeError::InvalidHex(s) => write!(
+                f,
+                "Hex chunk type must be 0x followed by 8 hex digits (got {})",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChunkTypeError {}
+
+impl TryFrom<[u8; 4]> for ChunkType {
+    type Error = ChunkTypeError;
+
+    fn try_from(bytes: [u8; 4]) -> Result<Self, Self::Error> {
+        Self::check_bytes(&bytes)?;
+        Ok(ChunkType { bytes })
+    }
+}
+
+impl TryFrom<&[u8]> for ChunkType {
+    type Error = ChunkTypeError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::check_bytes(bytes)?;
+        Ok(Self::from_checked_slice(bytes))
+    }
+}
+
+/// Parses a four-letter type code, ignoring surrounding ASCII whitespace.
+impl FromStr for ChunkType {
+    type Err = ChunkTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_matches(|c: char| c.is_ascii_whitespace());
+        Self::check_bytes(s.as_bytes())?;
+        Ok(Self::from_checked_slice(s.as_bytes()))
+    }
+}
+
+/// Serializes as the four-character type code, e.g. `"ruSt"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ChunkType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ChunkType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        ChunkType::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for ChunkType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+    use std::hash::{Hash, Hasher};
+    use std::str::FromStr;
+
+    #[test]
+    pub fn test_chunk_type_from_bytes() {
+        let expected = [82, 117, 83, 116];
+        let actual = ChunkType::try_from([82, 117, 83, 116]).unwrap();
+
+        assert_eq!(expected, actual.bytes());
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_slice() {
+        let bytes: &[u8] = &[82, 117, 83, 116];
+        let actual = ChunkType::try_from(bytes).unwrap();
+        assert_eq!(actual, ChunkType::from_str("RuSt").unwrap());
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_slice_invalid_length() {
+        let bytes: &[u8] = &[82, 117, 83, 116, 82, 117];
+        assert_eq!(
+            ChunkType::try_from(bytes).unwrap_err(),
+            ChunkTypeError::InvalidLength(6)
+        );
+        let bytes: &[u8] = &[];
+        assert_eq!(
+            ChunkType::try_from(bytes).unwrap_err(),
+            ChunkTypeError::InvalidLength(0)
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_slice_invalid_byte() {
+        let bytes: &[u8] = b"ru_t";
+        assert_eq!(
+            ChunkType::try_from(bytes).unwrap_err(),
+            ChunkTypeError::NonAsciiByte {
+                index: 2,
+                byte: b'_'
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_slice_invalid_utf8() {
+        let bytes: &[u8] = &[b'r', 0xff, b'S', b't'];
+        assert_eq!(
+            ChunkType::try_from(bytes).unwrap_err(),
+            ChunkTypeError::InvalidUtf8
+        );
+        // Valid UTF-8 that isn't ASCII still points at the offending byte.
+        assert_eq!(
+            ChunkType::try_from([b'r', b'u', 0xc3, 0xbc]).unwrap_err(),
+            ChunkTypeError::NonAsciiByte {
+                index: 2,
+                byte: 0xc3
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_from_str() {
+        let expected = ChunkType::try_from([82, 117, 83, 116]).unwrap();
+        let actual = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_critical() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert!(chunk.is_critical());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_not_critical() {
+        let chunk = ChunkType::from_str("ruSt").unwrap();
+        assert!(!chunk.is_critical());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_ancillary() {
+        let chunk = ChunkType::from_str("bLOb").unwrap();
+        assert!(chunk.is_ancillary());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_not_ancillary() {
+        let chunk = ChunkType::from_str("IHDR").unwrap();
+        assert!(!chunk.is_ancillary());
+    }
+
+    #[test]
+    pub fn test_chunk_type_to_ancillary() {
+        let chunk = ChunkType::from_str("IHDR").unwrap().to_ancillary();
+        assert_eq!(chunk.to_string(), "iHDR");
+        assert!(chunk.is_ancillary());
+        assert_eq!(chunk.to_ancillary(), chunk);
+    }
+
+    #[test]
+    pub fn test_chunk_type_to_critical() {
+        let chunk = ChunkType::from_str("ruSt").unwrap().to_critical();
+        assert_eq!(chunk.to_string(), "RuSt");
+        assert!(chunk.is_critical());
+        assert_eq!(chunk.to_critical(), chunk);
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_public() {
+        let chunk = ChunkType::from_str("RUSt").unwrap();
+        assert!(chunk.is_public());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_not_public() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert!(!chunk.is_public());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_private() {
+        let chunk = ChunkType::from_str("ruSt").unwrap();
+        assert!(chunk.is_private());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_not_private() {
+        let chunk = ChunkType::from_str("RUSt").unwrap();
+        assert!(!chunk.is_private());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_reserved_bit_valid() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert!(chunk.is_reserved_bit_valid());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_reserved_bit_invalid() {
+        let chunk = ChunkType::from_str("Rust").unwrap();
+        assert!(!chunk.is_reserved_bit_valid());
+    }
+
+    #[test]
+    pub fn test_chunk_type_property_bits() {
+        for s in ["RuSt", "ruSt", "RUST", "rust", "IHDR", "tEXt", "prVt"] {
+            let chunk = ChunkType::from_str(s).unwrap();
+            assert_eq!(chunk.property_bit(0), Some(!chunk.is_critical()), "{}", s);
+            assert_eq!(chunk.property_bit(1), Some(!chunk.is_public()), "{}", s);
+            assert_eq!(
+                chunk.property_bit(2),
+                Some(!chunk.is_reserved_bit_valid()),
+                "{}",
+                s
+            );
+            assert_eq!(
+                chunk.property_bit(3),
+                Some(chunk.is_safe_to_copy()),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(ChunkType::IEND.property_bit(4), None);
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_safe_to_copy() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert!(chunk.is_safe_to_copy());
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_unsafe_to_copy() {
+        let chunk = ChunkType::from_str("RuST").unwrap();
+        assert!(!chunk.is_safe_to_copy());
+    }
+
+    #[test]
+    pub fn test_chunk_type_properties() {
+        let chunk = ChunkType::from_str("bLOb").unwrap();
+        assert_eq!(
+            chunk.properties(),
+            ChunkProperties {
+                critical: false,
+                public: true,
+                reserved_valid: true,
+                safe_to_copy: true,
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_valid_chunk_is_valid() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert!(chunk.is_valid());
+    }
+
+    #[test]
+    pub fn test_invalid_chunk_is_valid() {
+        let chunk = ChunkType::from_str("Rust").unwrap();
+        assert!(!chunk.is_valid());
+
+        let chunk = ChunkType::from_str("Ru1t");
+        assert!(chunk.is_err());
+    }
+
+    #[test]
+    pub fn test_chunk_type_invalid_length() {
+        let err = ChunkType::from_str("ruStY").unwrap_err();
+        assert_eq!(err, ChunkTypeError::InvalidLength(5));
+    }
+
+    #[test]
+    pub fn test_chunk_type_non_ascii_byte() {
+        let err = ChunkType::from_str("Ru1t").unwrap_err();
+        assert_eq!(
+            err,
+            ChunkTypeError::NonAsciiByte {
+                index: 2,
+                byte: b'1'
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_string() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(&chunk.to_string(), "RuSt");
+    }
+
+    #[test]
+    pub fn test_chunk_type_trait_impls() {
+        let chunk_type_1: ChunkType = TryFrom::try_from([82, 117, 83, 116]).unwrap();
+        let chunk_type_2: ChunkType = FromStr::from_str("RuSt").unwrap();
+        let _chunk_string = format!("{}", chunk_type_1);
+        let _are_chunks_equal = chunk_type_1 == chunk_type_2;
+    }
+
+    #[test]
+    pub fn test_chunk_type_equal_values_hash_identically() {
+        let hash = |chunk_type: &ChunkType| {
+            let mut hasher = DefaultHasher::new();
+            chunk_type.hash(&mut hasher);
+            hasher.finish()
+        };
+        let a = ChunkType::from_str("ruSt").unwrap();
+        let b = ChunkType::try_from(*b"ruSt").unwrap();
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    pub fn test_chunk_type_as_hash_map_key() {
+        let rust = ChunkType::from_str("ruSt").unwrap();
+        let idat = ChunkType::from_str("IDAT").unwrap();
+
+        let mut counts: HashMap<ChunkType, usize> = HashMap::new();
+        for chunk_type in [rust, idat, rust] {
+            *counts.entry(chunk_type).or_insert(0) += 1;
+        }
+
+        assert_eq!(counts[&rust], 2);
+        assert_eq!(counts[&idat], 1);
+    }
+
+    #[test]
+    pub fn test_standard_chunk_type_constants() {
+        assert_eq!(ChunkType::IEND.to_string(), "IEND");
+        assert_eq!(ChunkType::IHDR, ChunkType::from_str("IHDR").unwrap());
+        assert_eq!(ChunkType::TEXT, ChunkType::from_str("tEXt").unwrap());
+        assert_eq!(ChunkType::ZTXT.to_string(), "zTXt");
+        assert_eq!(ChunkType::ITXT.to_string(), "iTXt");
+        for chunk_type in [
+            ChunkType::IHDR,
+            ChunkType::PLTE,
+            ChunkType::IDAT,
+            ChunkType::IEND,
+            ChunkType::TEXT,
+            ChunkType::ZTXT,
+            ChunkType::ITXT,
+        ] {
+            assert!(chunk_type.is_valid());
+        }
+    }
+
+    #[test]
+    pub fn test_standard_chunk_type_in_match() {
+        let chunk_type = ChunkType::from_str("IDAT").unwrap();
+        let name = match chunk_type {
+            ChunkType::IHDR => "header",
+            ChunkType::IDAT => "data",
+            _ => "other",
+        };
+        assert_eq!(name, "data");
+    }
+
+    #[test]
+    pub fn test_chunk_type_sorts_by_bytes() {
+        let mut chunk_types: Vec<ChunkType> = ["tEXt", "IHDR", "IDAT"]
+            .iter()
+            .map(|s| ChunkType::from_str(s).unwrap())
+            .collect();
+        chunk_types.sort();
+
+        let sorted: Vec<String> = chunk_types.iter().map(ChunkType::to_string).collect();
+        assert_eq!(sorted, ["IDAT", "IHDR", "tEXt"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    pub fn test_chunk_type_serde() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let json = serde_json::to_string(&chunk_type).unwrap();
+        assert_eq!(json, "\"ruSt\"");
+        assert_eq!(
+            serde_json::from_str::<ChunkType>(&json).unwrap(),
+            chunk_type
+        );
+        assert!(serde_json::from_str::<ChunkType>("\"ru1t\"").is_err());
+    }
+
+    #[test]
+    pub fn test_validate_with_detail() {
+        assert!(ChunkType::validate_with_detail("ruSt").is_ok());
+        assert_eq!(
+            ChunkType::validate_with_detail("ruS!"),
+            Err(ChunkTypeError::NonAsciiByte {
+                index: 3,
+                byte: b'!'
+            })
+        );
+        assert_eq!(
+            ChunkType::validate_with_detail("9uSt"),
+            Err(ChunkTypeError::NonAsciiByte {
+                index: 0,
+                byte: b'9'
+            })
+        );
+        assert_eq!(
+            ChunkType::validate_with_detail("rüt"),
+            Err(ChunkTypeError::NonAsciiByte {
+                index: 1,
+                byte: 0xc3
+            })
+        );
+        assert_eq!(
+            ChunkType::validate_with_detail("ru"),
+            Err(ChunkTypeError::InvalidLength(2))
+        );
+    }
+
+    #[test]
+    pub fn test_from_str_matches_validate_with_detail() {
+        for s in ["ruSt", "ru1t", "r", "ruSty", "    ", " ru1t\n"] {
+            assert_eq!(
+                ChunkType::from_str(s).err(),
+                ChunkType::validate_with_detail(s).err()
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_from_str_trims_whitespace() {
+        let expected = ChunkType::from_str("ruSt").unwrap();
+        assert_eq!(ChunkType::from_str("ruSt\n").unwrap(), expected);
+        assert_eq!(ChunkType::from_str(" ruSt ").unwrap(), expected);
+        assert_eq!(ChunkType::from_str("\truSt\r\n").unwrap(), expected);
+        assert_eq!(
+            ChunkType::from_str("ru St"),
+            Err(ChunkTypeError::InvalidLength(5))
+        );
+        assert_eq!(
+            ChunkType::from_str(" ru\n"),
+            Err(ChunkTypeError::InvalidLength(2))
+        );
+    }
+
+    #[test]
+    pub fn test_as_str() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        assert_eq!(chunk_type.as_str(), "ruSt");
+        assert_eq!(chunk_type.as_str(), chunk_type.to_string());
+    }
+
+    #[test]
+    pub fn test_description() {
+        assert_eq!(ChunkType::IHDR.description(), Some("Image header"));
+        assert_eq!(ChunkType::TEXT.description(), Some("Textual data"));
+        assert_eq!(
+            ChunkType::from_str("gAMA").unwrap().description(),
+            Some("Image gamma")
+        );
+        assert_eq!(ChunkType::from_str("ruSt").unwrap().description(), None);
+        // The case of each letter matters, as it does for the type itself.
+        assert_eq!(ChunkType::from_str("GAMA").unwrap().description(), None);
+    }
+
+    #[test]
+    pub fn test_description_table_is_valid() {
+        for (bytes, _) in DESCRIPTIONS {
+            assert!(
+                ChunkType::try_from(*bytes).unwrap().is_valid(),
+                "{:?}",
+                bytes
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_from_bytes_unchecked() {
+        assert_eq!(
+            ChunkType::from_bytes_unchecked(*b"ruSt"),
+            ChunkType::try_from(*b"ruSt").unwrap()
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "chunk type bytes must be ASCII letters")]
+    pub fn test_from_bytes_unchecked_checks_in_debug_builds() {
+        ChunkType::from_bytes_unchecked(*b"ru1t");
+    }
+
+    proptest! {
+        #[test]
+        fn test_from_str_round_trip(s in "[a-zA-Z]{4}") {
+            let chunk_type = ChunkType::from_str(&s).unwrap();
+            prop_assert_eq!(chunk_type.to_string(), s.clone());
+            prop_assert_eq!(ChunkType::from_str(&chunk_type.to_string()), Ok(chunk_type));
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! CRC-32 (ISO-HDLC) as used by PNG chunk checksums.
+
+/// Reversed form of the CRC-32 polynomial used by PNG.
+const POLYNOMIAL: u32 = 0xedb8_8320;
+
+/// Lookup tables for slice-by-8 CRC computation, built at compile time.
+///
+/// `TABLES[0]` is the classic byte-at-a-time table. `TABLES[k][n]` is the CRC of byte
+/// `n` followed by `k` zero bytes, which lets eight input bytes be folded in at once.
+const TABLES: [[u32; 256]; 8] = make_tables();
+
+const fn make_tables() -> [[u32; 256]; 8] {
+    let mut tables = [[0u32; 256]; 8];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                POLYNOMIAL ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        tables[0][n] = c;
+        n += 1;
+    }
+
+    let mut k = 1;
+    while k < 8 {
+        let mut n = 0;
+        while n < 256 {
+            let prev = tables[k - 1][n];
+            tables[k][n] = (prev >> 8) ^ tables[0][(prev & 0xff) as usize];
+            n += 1;
+        }
+        k += 1;
+    }
+    tables
+}
+
+/// Computes the CRC-32 of `bytes`.
+///
+/// For a PNG chunk this is taken over the chunk type followed by the chunk data.
+/// Eight bytes are processed per step using the slice-by-8 tables.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    Crc32::new().update(bytes).finalize()
+}
+
+/// A CRC-32 computed incrementally, for data that arrives in pieces, e.g. from a reader.
+///
+/// Feeding the pieces in order through [`Crc32::update`] gives the same result as
+/// [`crc32`] over their concatenation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    /// The running register, before the final inversion.
+    state: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Crc32 {
+        Crc32 { state: 0xffff_ffff }
+    }
+
+    /// Folds `bytes` into the CRC.
+    #[must_use]
+    pub fn update(self, bytes: &[u8]) -> Crc32 {
+        Crc32 {
+            state: update_sliced(self.state, bytes),
+        }
+    }
+
+    /// The CRC of every byte passed to [`Crc32::update`] so far.
+    pub fn finalize(self) -> u32 {
+        self.state ^ 0xffff_ffff
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Crc32::new()
+    }
+}
+
+fn update_sliced(mut crc: u32, bytes: &[u8]) -> u32 {
+    let mut blocks = bytes.chunks_exact(8);
+    for block in &mut blocks {
+        let lo = u32::from_le_bytes([block[0], block[1], block[2], block[3]]) ^ crc;
+        let hi = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+        crc = TABLES[7][(lo & 0xff) as usize]
+            ^ TABLES[6][((lo >> 8) & 0xff) as usize]
+            ^ TABLES[5][((lo >> 16) & 0xff) as usize]
+            ^ TABLES[4][(lo >> 24) as usize]
+            ^ TABLES[3][(hi & 0xff) as usize]
+            ^ TABLES[2][((hi >> 8) & 0xff) as usize]
+            ^ TABLES[1][((hi >> 16) & 0xff) as usize]
+            ^ TABLES[0][(hi >> 24) as usize];
+    }
+    update_bytewise(crc, blocks.remainder())
+}
+
+/// Computes the CRC-32 of `bytes` one byte at a time.
+///
+/// Gives the same result as [`crc32`] but several times slower; it is the reference the
+/// fast version is tested and benchmarked against.
+pub fn crc32_bytewise(bytes: &[u8]) -> u32 {
+    update_bytewise(0xffff_ffff, bytes) ^ 0xffff_ffff
+}
+
+fn update_bytewise(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        TABLES[0][((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_empty() {
+        assert_eq!(crc32(&[]), 0);
+    }
+
+    #[test]
+    fn test_crc32_iend() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_crc32_bytewise_check_value() {
+        assert_eq!(crc32_bytewise(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_crc32_streaming_matches_one_shot() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for split in [0, 1, 7, 100, 256] {
+            let (a, b) = bytes.split_at(split);
+            assert_eq!(Crc32::new().update(a).update(b).finalize(), crc32(&bytes));
+        }
+        assert_eq!(Crc32::default().finalize(), crc32(&[]));
+    }
+
+    #[test]
+    fn test_slice_by_8_matches_bytewise() {
+        // xorshift32, so the inputs are varied but the test stays deterministic.
+        let mut state = 0x9e37_79b9_u32;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        for _ in 0..500 {
+            let len = (next() % 300) as usize;
+            let bytes: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+            // Every offset exercises a different split between blocks and remainder.
+            let offset = len.min((next() % 8) as usize);
+            let bytes = &bytes[offset..];
+            assert_eq!(crc32(bytes), crc32_bytewise(bytes), "{:?}", bytes);
+        }
+    }
+}
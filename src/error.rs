@@ -0,0 +1,168 @@
+use std::fmt;
+use std::io;
+
+use crate::chunk::ChunkError;
+use crate::chunk_type::{ChunkType, ChunkTypeError};
+use crate::ihdr::IhdrError;
+use crate::itxt::ITxtError;
+use crate::palette::PaletteError;
+use crate::payload::PayloadError;
+use crate::png::PngError;
+use crate::text::TextError;
+use crate::ztxt::ZTxtError;
+
+/// Any error produced by this crate.
+#[derive(Debug)]
+pub enum Error {
+    ChunkType(ChunkTypeError),
+    Chunk(ChunkError),
+    Png(PngError),
+    Ihdr(IhdrError),
+    Text(TextError),
+    ITxt(ITxtError),
+    ZTxt(ZTxtError),
+    Palette(PaletteError),
+    Payload(PayloadError),
+    Io(io::Error),
+    /// Encoding into a critical chunk type was refused because `--force` wasn't given.
+    CriticalChunkType(ChunkType),
+    /// Some files of a directory batch failed; each was already reported on its own.
+    BatchFailed {
+        failed: usize,
+        total: usize,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ChunkType(e) => write!(f, "{}", e),
+            Error::Chunk(e) => write!(f, "{}", e),
+            Error::Png(e) => write!(f, "{}", e),
+            Error::Ihdr(e) => write!(f, "{}", e),
+            Error::Text(e) => write!(f, "{}", e),
+            Error::ITxt(e) => write!(f, "{}", e),
+            Error::ZTxt(e) => write!(f, "{}", e),
+            Error::Palette(e) => write!(f, "{}", e),
+            Error::Payload(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::CriticalChunkType(chunk_type) => write!(
+                f,
+                "Refusing to hide a message in {}: it is a critical chunk type, which \
+                 decoders must understand, so the image may no longer open. Use an \
+                 ancillary type such as {} or pass --force",
+                chunk_type,
+                chunk_type.to_ancillary()
+            ),
+            Error::BatchFailed { failed, total } => {
+                write!(f, "Failed to encode {} of {} files", failed, total)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ChunkType(e) => Some(e),
+            Error::Chunk(e) => Some(e),
+            Error::Png(e) => Some(e),
+            Error::Ihdr(e) => Some(e),
+            Error::Text(e) => Some(e),
+            Error::ITxt(e) => Some(e),
+            Error::ZTxt(e) => Some(e),
+            Error::Palette(e) => Some(e),
+            Error::Payload(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::CriticalChunkType(_) | Error::BatchFailed { .. } => None,
+        }
+    }
+}
+
+impl From<ChunkTypeError> for Error {
+    fn from(e: ChunkTypeError) -> Self {
+        Error::ChunkType(e)
+    }
+}
+
+impl From<ChunkError> for Error {
+    fn from(e: ChunkError) -> Self {
+        Error::Chunk(e)
+    }
+}
+
+impl From<PngError> for Error {
+    fn from(e: PngError) -> Self {
+        Error::Png(e)
+    }
+}
+
+impl From<IhdrError> for Error {
+    fn from(e: IhdrError) -> Self {
+        Error::Ihdr(e)
+    }
+}
+
+impl From<TextError> for Error {
+    fn from(e: TextError) -> Self {
+        Error::Text(e)
+    }
+}
+
+impl From<ITxtError> for Error {
+    fn from(e: ITxtError) -> Self {
+        Error::ITxt(e)
+    }
+}
+
+impl From<ZTxtError> for Error {
+    fn from(e: ZTxtError) -> Self {
+        Error::ZTxt(e)
+    }
+}
+
+impl From<PaletteError> for Error {
+    fn from(e: PaletteError) -> Self {
+        Error::Palette(e)
+    }
+}
+
+impl From<PayloadError> for Error {
+    fn from(e: PayloadError) -> Self {
+        Error::Payload(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn parse_chunk_type(s: &str) -> Result<ChunkType> {
+        Ok(ChunkType::from_str(s)?)
+    }
+
+    #[test]
+    fn test_question_mark_converts_module_errors() {
+        let err = parse_chunk_type("ru").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::ChunkType(ChunkTypeError::InvalidLength(2))
+        ));
+    }
+
+    #[test]
+    fn test_display_delegates_to_inner_error() {
+        let err = Error::from(PngError::InvalidHeader);
+        assert_eq!(err.to_string(), PngError::InvalidHeader.to_string());
+    }
+}
@@ -0,0 +1,306 @@
+//! `IHDR` chunks: the image width, height, bit depth, color type and interlace method.
+
+use std::fmt;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+
+/// The image metadata stored in an `IHDR` chunk.
+///
+/// The color type and interlace method must be ones the PNG specification defines; the
+/// other values are reported as stored, and nothing checks that e.g. the bit depth is
+/// allowed for the color type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageHeader {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub color_type: ColorType,
+    pub compression: u8,
+    pub filter: u8,
+    pub interlace: Interlace,
+}
+
+/// How each pixel is stored, from the `IHDR` color type byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorType {
+    Grayscale,
+    Rgb,
+    /// Indexes into the palette in the `PLTE` chunk, which must be present.
+    Palette,
+    GrayscaleAlpha,
+    Rgba,
+}
+
+impl TryFrom<u8> for ColorType {
+    type Error = IhdrError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0 => Ok(ColorType::Grayscale),
+            2 => Ok(ColorType::Rgb),
+            3 => Ok(ColorType::Palette),
+            4 => Ok(ColorType::GrayscaleAlpha),
+            6 => Ok(ColorType::Rgba),
+            _ => Err(IhdrError::InvalidColorType(byte)),
+        }
+    }
+}
+
+impl From<ColorType> for u8 {
+    fn from(color_type: ColorType) -> u8 {
+        match color_type {
+            ColorType::Grayscale => 0,
+            ColorType::Rgb => 2,
+            ColorType::Palette => 3,
+            ColorType::GrayscaleAlpha => 4,
+            ColorType::Rgba => 6,
+        }
+    }
+}
+
+impl fmt::Display for ColorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColorType::Grayscale => "Grayscale",
+            ColorType::Rgb => "RGB",
+            ColorType::Palette => "Palette",
+            ColorType::GrayscaleAlpha => "Grayscale+Alpha",
+            ColorType::Rgba => "RGBA",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The order pixels are stored in, from the `IHDR` interlace method byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interlace {
+    None,
+    Adam7,
+}
+
+impl TryFrom<u8> for Interlace {
+    type Error = IhdrError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0 => Ok(Interlace::None),
+            1 => Ok(Interlace::Adam7),
+            _ => Err(IhdrError::InvalidInterlace(byte)),
+        }
+    }
+}
+
+impl From<Interlace> for u8 {
+    fn from(interlace: Interlace) -> u8 {
+        match interlace {
+            Interlace::None => 0,
+            Interlace::Adam7 => 1,
+        }
+    }
+}
+
+impl fmt::Display for Interlace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interlace::None => f.write_str("None"),
+            Interlace::Adam7 => f.write_str("Adam7"),
+        }
+    }
+}
+
+impl ImageHeader {
+    /// Length of the `IHDR` chunk data in bytes.
+    pub const LENGTH: usize = 13;
+
+    /// Parses the header from an `IHDR` chunk.
+    pub fn from_chunk(chunk: &Chunk) -> crate::Result<ImageHeader> {
+        Ok(Self::parse(chunk)?)
+    }
+
+    /// [`ImageHeader::from_chunk`] with the module error, for callers that wrap it in
+    /// their own.
+    pub(crate) fn parse(chunk: &Chunk) -> Result<ImageHeader, IhdrError> {
+        if *chunk.chunk_type() != ChunkType::IHDR {
+            return Err(IhdrError::WrongChunkType(*chunk.chunk_type()));
+        }
+        let data = chunk.data();
+        if data.len() != Self::LENGTH {
+            return Err(IhdrError::InvalidLength(data.len()));
+        }
+        Ok(ImageHeader {
+            width: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            height: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            bit_depth: data[8],
+            color_type: ColorType::try_from(data[9])?,
+            compression: data[10],
+            filter: data[11],
+            interlace: Interlace::try_from(data[12])?,
+        })
+    }
+
+    /// Serializes the header as an `IHDR` chunk; the inverse of [`ImageHeader::from_chunk`].
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(Self::LENGTH);
+        data.extend_from_slice(&self.width.to_be_bytes());
+        data.extend_from_slice(&self.height.to_be_bytes());
+        data.extend_from_slice(&[
+            self.bit_depth,
+            self.color_type.into(),
+            self.compression,
+            self.filter,
+            self.interlace.into(),
+        ]);
+        Chunk::new(ChunkType::IHDR, data)
+    }
+}
+
+/// Errors produced when parsing an [`ImageHeader`].
+#[derive(Debug)]
+pub enum IhdrError {
+    /// The chunk is not an `IHDR` chunk; carries its actual type.
+    WrongChunkType(ChunkType),
+    /// The chunk data is not [`ImageHeader::LENGTH`] bytes long; carries the actual length.
+    InvalidLength(usize),
+    /// The color type byte is not one the PNG specification defines.
+    InvalidColorType(u8),
+    /// The interlace method byte is neither 0 (none) nor 1 (Adam7).
+    InvalidInterlace(u8),
+}
+
+impl fmt::Display for IhdrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IhdrError::WrongChunkType(chunk_type) => {
+                write!(f, "Expected an IHDR chunk, found {}", chunk_type)
+            }
+            IhdrError::InvalidLength(len) => write!(
+                f,
+                "IHDR data must be {} bytes long, found {}",
+                ImageHeader::LENGTH,
+                len
+            ),
+            IhdrError::InvalidColorType(byte) => write!(f, "Invalid IHDR color type {}", byte),
+            IhdrError::InvalidInterlace(byte) => {
+                write!(f, "Invalid IHDR interlace method {}", byte)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IhdrError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::str::FromStr;
+
+    /// IHDR data for a 640x480 8-bit RGBA image with Adam7 interlacing.
+    const IHDR_DATA: [u8; 13] = [0, 0, 2, 128, 0, 0, 1, 224, 8, 6, 0, 0, 1];
+
+    #[test]
+    fn test_from_chunk() {
+        let chunk = Chunk::new(ChunkType::IHDR, IHDR_DATA.to_vec());
+        let header = ImageHeader::from_chunk(&chunk).unwrap();
+        assert_eq!(
+            header,
+            ImageHeader {
+                width: 640,
+                height: 480,
+                bit_depth: 8,
+                color_type: ColorType::Rgba,
+                compression: 0,
+                filter: 0,
+                interlace: Interlace::Adam7,
+            }
+        );
+    }
+
+    #[test]
+    fn test_to_chunk_round_trip() {
+        let chunk = Chunk::new(ChunkType::IHDR, IHDR_DATA.to_vec());
+        let header = ImageHeader::from_chunk(&chunk).unwrap();
+        assert_eq!(header.to_chunk(), chunk);
+    }
+
+    #[test]
+    fn test_color_type_from_byte() {
+        for (byte, color_type) in [
+            (0, ColorType::Grayscale),
+            (2, ColorType::Rgb),
+            (3, ColorType::Palette),
+            (4, ColorType::GrayscaleAlpha),
+            (6, ColorType::Rgba),
+        ] {
+            assert_eq!(ColorType::try_from(byte).unwrap(), color_type);
+            assert_eq!(u8::from(color_type), byte);
+        }
+        for byte in [1, 5, 7, 255] {
+            assert!(matches!(
+                ColorType::try_from(byte),
+                Err(IhdrError::InvalidColorType(b)) if b == byte
+            ));
+        }
+    }
+
+    #[test]
+    fn test_interlace_from_byte() {
+        for (byte, interlace) in [(0, Interlace::None), (1, Interlace::Adam7)] {
+            assert_eq!(Interlace::try_from(byte).unwrap(), interlace);
+            assert_eq!(u8::from(interlace), byte);
+        }
+        assert!(matches!(
+            Interlace::try_from(2),
+            Err(IhdrError::InvalidInterlace(2))
+        ));
+    }
+
+    #[test]
+    fn test_from_chunk_rejects_unknown_values() {
+        let mut data = IHDR_DATA;
+        data[9] = 5;
+        assert!(matches!(
+            ImageHeader::from_chunk(&Chunk::new(ChunkType::IHDR, data.to_vec())),
+            Err(Error::Ihdr(IhdrError::InvalidColorType(5)))
+        ));
+
+        let mut data = IHDR_DATA;
+        data[12] = 2;
+        assert!(matches!(
+            ImageHeader::from_chunk(&Chunk::new(ChunkType::IHDR, data.to_vec())),
+            Err(Error::Ihdr(IhdrError::InvalidInterlace(2)))
+        ));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ColorType::Rgba.to_string(), "RGBA");
+        assert_eq!(ColorType::GrayscaleAlpha.to_string(), "Grayscale+Alpha");
+        assert_eq!(Interlace::Adam7.to_string(), "Adam7");
+    }
+
+    #[test]
+    fn test_from_chunk_wrong_type() {
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), IHDR_DATA.to_vec());
+        assert!(matches!(
+            ImageHeader::from_chunk(&chunk),
+            Err(Error::Ihdr(IhdrError::WrongChunkType(t))) if t.to_string() == "ruSt"
+        ));
+    }
+
+    #[test]
+    fn test_from_chunk_invalid_length() {
+        let chunk = Chunk::new(ChunkType::IHDR, IHDR_DATA[..12].to_vec());
+        assert!(matches!(
+            ImageHeader::from_chunk(&chunk),
+            Err(Error::Ihdr(IhdrError::InvalidLength(12)))
+        ));
+
+        let chunk = Chunk::new(ChunkType::IHDR, [&IHDR_DATA[..], &[0]].concat());
+        assert!(matches!(
+            ImageHeader::from_chunk(&chunk),
+            Err(Error::Ihdr(IhdrError::InvalidLength(14)))
+        ));
+    }
+}
@@ -0,0 +1,266 @@
+//! Spec-compliant `iTXt` (international text) chunks.
+//!
+//! The chunk data is the keyword, a NUL, the compression flag and method bytes, the
+//! language tag, a NUL, the translated keyword, a NUL, then the text. The keyword is
+//! Latin-1; the translated keyword and text are UTF-8.
+
+use std::fmt;
+use std::io;
+
+use flate2::read::ZlibDecoder;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::payload::inflate;
+use crate::text::{decode_latin1, encode_keyword, MAX_KEYWORD_LEN};
+
+/// The fields of an `iTXt` chunk, as returned by [`parse_itxt`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ITxtData {
+    pub keyword: String,
+    /// Whether the text was stored zlib-compressed. [`parse_itxt`] has already inflated it.
+    pub compressed: bool,
+    /// RFC 3066 language tag such as `en-US`; empty if unspecified.
+    pub language_tag: String,
+    /// The keyword translated into the language; empty if unspecified.
+    pub translated_keyword: String,
+    pub text: String,
+}
+
+impl Chunk {
+    /// Creates an uncompressed `iTXt` chunk holding `text` under `keyword`, with empty
+    /// language tag and translated keyword.
+    ///
+    /// The keyword must be 1 to 79 printable Latin-1 characters with no leading, trailing
+    /// or consecutive spaces.
+    pub fn new_itxt(keyword: &str, text: &str) -> crate::Result<Chunk> {
+        let mut data =
+            encode_keyword(keyword).ok_or_else(|| ITxtError::InvalidKeyword(keyword.into()))?;
+        // NUL separator, compression flag and method, then empty language tag and
+        // translated keyword, each followed by its NUL.
+        data.extend_from_slice(&[0, 0, 0, 0, 0]);
+        data.extend_from_slice(text.as_bytes());
+        Ok(Chunk::new(ChunkType::ITXT, data))
+    }
+}
+
+/// Parses the fields of an `iTXt` chunk, inflating the text if it is compressed.
+pub fn parse_itxt(chunk: &Chunk) -> crate::Result<ITxtData> {
+    Ok(parse(chunk)?)
+}
+
+fn parse(chunk: &Chunk) -> Result<ITxtData, ITxtError> {
+    if *chunk.chunk_type() != ChunkType::ITXT {
+        return Err(ITxtError::WrongChunkType(*chunk.chunk_type()));
+    }
+
+    let (keyword, rest) = split_at_nul(chunk.data())?;
+    let keyword = decode_latin1(keyword);
+    let (flag, method, rest) = match rest {
+        [flag, method, rest @ ..] => (*flag, *method, rest),
+        _ => return Err(ITxtError::Truncated),
+    };
+    let (language_tag, rest) = split_at_nul(rest)?;
+    let (translated_keyword, text) = split_at_nul(rest)?;
+
+    let compressed = match (flag, method) {
+        (0, _) => false,
+        (1, 0) => true,
+        (1, method) => return Err(ITxtError::UnsupportedCompression(method)),
+        (flag, _) => return Err(ITxtError::InvalidCompressionFlag(flag)),
+    };
+    let text = if compressed {
+        inflate(ZlibDecoder::new(text)).map_err(ITxtError::Decompress)?
+    } else {
+        text.to_vec()
+    };
+
+    Ok(ITxtData {
+        keyword,
+        compressed,
+        language_tag: utf8(language_tag)?,
+        translated_keyword: utf8(translated_keyword)?,
+        text: String::from_utf8(text).map_err(|_| ITxtError::InvalidUtf8)?,
+    })
+}
+
+/// Splits `bytes` at the first NUL, dropping the NUL itself.
+fn split_at_nul(bytes: &[u8]) -> Result<(&[u8], &[u8]), ITxtError> {
+    let nul = bytes
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(ITxtError::Truncated)?;
+    Ok((&bytes[..nul], &bytes[nul + 1..]))
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, ITxtError> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| ITxtError::InvalidUtf8)
+}
+
+/// Errors produced when building or parsing an `iTXt` chunk.
+#[derive(Debug)]
+pub enum ITxtError {
+    /// The keyword breaks the spec's length or character rules; carries the keyword.
+    InvalidKeyword(String),
+    /// The chunk is not an `iTXt` chunk; carries its actual type.
+    WrongChunkType(ChunkType),
+    /// The data ended before every field and separator was found.
+    Truncated,
+    /// The compression flag is neither 0 nor 1; carries the flag.
+    InvalidCompressionFlag(u8),
+    /// The text is compressed with a method other than zlib; carries the method.
+    UnsupportedCompression(u8),
+    /// The compressed text could not be inflated.
+    Decompress(io::Error),
+    /// The language tag, translated keyword or text is not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl fmt::Display for ITxtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ITxtError::InvalidKeyword(keyword) => write!(
+                f,
+                "Invalid iTXt keyword {:?}: must be 1 to {} printable Latin-1 characters \
+                 without leading, trailing or repeated spaces",
+                keyword, MAX_KEYWORD_LEN
+            ),
+            ITxtError::WrongChunkType(chunk_type) => {
+                write!(f, "Expected an iTXt chunk, found {}", chunk_type)
+            }
+            ITxtError::Truncated => write!(f, "iTXt chunk is missing a field or separator"),
+            ITxtError::InvalidCompressionFlag(flag) => {
+                write!(f, "Invalid iTXt compression flag {}", flag)
+            }
+            ITxtError::UnsupportedCompression(method) => {
+                write!(f, "Unsupported iTXt compression method {}", method)
+            }
+            ITxtError::Decompress(e) => write!(f, "Failed to decompress iTXt text: {}", e),
+            ITxtError::InvalidUtf8 => write!(f, "iTXt text field is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for ITxtError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ITxtError::Decompress(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::io::Write;
+    use std::str::FromStr;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    #[test]
+    fn test_new_itxt_layout() {
+        let chunk = Chunk::new_itxt("Comment", "héllo").unwrap();
+        assert_eq!(*chunk.chunk_type(), ChunkType::ITXT);
+        assert_eq!(chunk.data(), b"Comment\0\0\0\0\0h\xc3\xa9llo");
+    }
+
+    #[test]
+    fn test_itxt_round_trip() {
+        let chunk = Chunk::new_itxt("Caf\u{e9}", "secret message").unwrap();
+        assert_eq!(&chunk.data()[..5], b"Caf\xe9\0");
+        assert_eq!(
+            parse_itxt(&chunk).unwrap(),
+            ITxtData {
+                keyword: "Caf\u{e9}".to_string(),
+                compressed: false,
+                language_tag: String::new(),
+                translated_keyword: String::new(),
+                text: "secret message".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_itxt_with_language_and_compression() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all("grüße".as_bytes()).unwrap();
+        let data = [
+            &b"Title\0\x01\x00de-DE\0Titel\0"[..],
+            &encoder.finish().unwrap(),
+        ]
+        .concat();
+
+        let itxt = parse_itxt(&Chunk::new(ChunkType::ITXT, data)).unwrap();
+        assert!(itxt.compressed);
+        assert_eq!(itxt.language_tag, "de-DE");
+        assert_eq!(itxt.translated_keyword, "Titel");
+        assert_eq!(itxt.text, "grüße");
+    }
+
+    #[test]
+    fn test_parse_itxt_empty_text() {
+        let itxt = parse_itxt(&Chunk::new_itxt("Comment", "").unwrap()).unwrap();
+        assert_eq!(itxt.text, "");
+    }
+
+    #[test]
+    fn test_new_itxt_rejects_invalid_keywords() {
+        for keyword in [
+            "",
+            " lead",
+            "trail ",
+            "two  spaces",
+            "tab\there",
+            "\u{263a}",
+        ] {
+            assert!(
+                matches!(
+                    Chunk::new_itxt(keyword, "text"),
+                    Err(Error::ITxt(ITxtError::InvalidKeyword(_)))
+                ),
+                "{:?}",
+                keyword
+            );
+        }
+        assert!(Chunk::new_itxt(&"k".repeat(79), "text").is_ok());
+        assert!(Chunk::new_itxt(&"k".repeat(80), "text").is_err());
+    }
+
+    #[test]
+    fn test_parse_itxt_errors() {
+        let chunk = Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"a\0b".to_vec());
+        assert!(matches!(
+            parse_itxt(&chunk),
+            Err(Error::ITxt(ITxtError::WrongChunkType(ChunkType::TEXT)))
+        ));
+
+        for data in [&b"no separator"[..], b"key\0", b"key\0\0\0en"] {
+            let chunk = Chunk::new(ChunkType::ITXT, data.to_vec());
+            assert!(matches!(
+                parse_itxt(&chunk),
+                Err(Error::ITxt(ITxtError::Truncated))
+            ));
+        }
+
+        let chunk = Chunk::new(ChunkType::ITXT, b"key\0\x02\0\0\0text".to_vec());
+        assert!(matches!(
+            parse_itxt(&chunk),
+            Err(Error::ITxt(ITxtError::InvalidCompressionFlag(2)))
+        ));
+
+        let chunk = Chunk::new(ChunkType::ITXT, b"key\0\x01\x07\0\0text".to_vec());
+        assert!(matches!(
+            parse_itxt(&chunk),
+            Err(Error::ITxt(ITxtError::UnsupportedCompression(7)))
+        ));
+
+        let chunk = Chunk::new(ChunkType::ITXT, b"key\0\0\0\0\0\xff".to_vec());
+        assert!(matches!(
+            parse_itxt(&chunk),
+            Err(Error::ITxt(ITxtError::InvalidUtf8))
+        ));
+    }
+}
@@ -0,0 +1,31 @@
+//! `pngme` hides secret messages inside the chunks of PNG files.
+
+pub mod args;
+mod chunk;
+mod chunk_type;
+pub mod commands;
+mod crc;
+mod error;
+mod ihdr;
+mod itxt;
+mod log;
+mod palette;
+pub mod payload;
+mod png;
+mod text;
+mod ztxt;
+
+pub use chunk::{Chunk, ChunkError};
+pub use chunk_type::{ChunkProperties, ChunkType, ChunkTypeError};
+pub use crc::{crc32, crc32_bytewise, Crc32};
+pub use error::{Error, Result};
+pub use ihdr::{ColorType, IhdrError, ImageHeader, Interlace};
+pub use itxt::{parse_itxt, ITxtData, ITxtError};
+pub use log::Verbosity;
+pub use palette::{parse_palette, PaletteError, MAX_PALETTE_ENTRIES};
+pub use png::{
+    has_png_signature, is_png, verify_bytes, CapacityLimits, Png, PngBuilder, PngDiff, PngError,
+    PngReadOptions,
+};
+pub use text::{parse_text, TextEntry, TextError};
+pub use ztxt::{parse_ztxt, ZTxtError};
@@ -0,0 +1,54 @@
+use std::fmt;
+use std::io::{self, Write};
+
+/// How much the commands report beyond their actual output, set by `--quiet` and
+/// `--verbose`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    /// Suppress success messages; errors are still reported.
+    Quiet,
+    #[default]
+    Normal,
+    /// Also log each chunk a command acts on. Commands that only report, such as
+    /// `print`, already list every chunk and log nothing more.
+    Verbose,
+}
+
+impl Verbosity {
+    /// Writes a success message such as "Removed 2 ancillary chunks" to `out`, unless
+    /// quiet.
+    pub fn status(self, out: &mut impl Write, message: fmt::Arguments<'_>) -> io::Result<()> {
+        if self == Verbosity::Quiet {
+            return Ok(());
+        }
+        writeln!(out, "{}", message)
+    }
+
+    /// Writes a diagnostic line to stderr when verbose. Stderr keeps stdout free for a
+    /// PNG streamed there with `-`.
+    pub fn trace(self, message: fmt::Arguments<'_>) {
+        if self == Verbosity::Verbose {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_is_suppressed_only_when_quiet() {
+        for (verbosity, expected) in [
+            (Verbosity::Quiet, ""),
+            (Verbosity::Normal, "Removed 2 ancillary chunks\n"),
+            (Verbosity::Verbose, "Removed 2 ancillary chunks\n"),
+        ] {
+            let mut out = Vec::new();
+            verbosity
+                .status(&mut out, format_args!("Removed {} ancillary chunks", 2))
+                .unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+}
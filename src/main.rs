@@ -0,0 +1,18 @@
+use std::io;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use pngme::args::Cli;
+use pngme::commands;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match commands::run(&cli.command, cli.verbosity(), &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! `PLTE` chunks: the RGB color table of a palette-indexed image.
+
+use std::fmt;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+
+/// Most entries a palette may hold, since indexes are at most eight bits.
+pub const MAX_PALETTE_ENTRIES: usize = 256;
+
+/// Parses the entries of a `PLTE` chunk as RGB triples, in index order.
+///
+/// The data must be a nonzero multiple of three bytes, at most
+/// [`MAX_PALETTE_ENTRIES`] entries long.
+pub fn parse_palette(chunk: &Chunk) -> crate::Result<Vec<[u8; 3]>> {
+    if *chunk.chunk_type() != ChunkType::PLTE {
+        return Err(PaletteError::WrongChunkType(*chunk.chunk_type()).into());
+    }
+    let data = chunk.data();
+    if data.is_empty() || !data.len().is_multiple_of(3) || data.len() > MAX_PALETTE_ENTRIES * 3 {
+        return Err(PaletteError::InvalidLength(data.len()).into());
+    }
+    Ok(data
+        .chunks_exact(3)
+        .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+        .collect())
+}
+
+/// Errors produced when parsing a `PLTE` chunk.
+#[derive(Debug)]
+pub enum PaletteError {
+    /// The chunk is not a `PLTE` chunk; carries its actual type.
+    WrongChunkType(ChunkType),
+    /// The data is empty, not a multiple of three bytes, or too long; carries its length.
+    InvalidLength(usize),
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::WrongChunkType(chunk_type) => {
+                write!(f, "Expected a PLTE chunk, found {}", chunk_type)
+            }
+            PaletteError::InvalidLength(len) => write!(
+                f,
+                "PLTE data must be a nonzero multiple of 3 bytes, at most {}, found {}",
+                MAX_PALETTE_ENTRIES * 3,
+                len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_palette() {
+        let chunk = Chunk::new(ChunkType::PLTE, vec![0, 0, 0, 255, 128, 0]);
+        assert_eq!(parse_palette(&chunk).unwrap(), [[0, 0, 0], [255, 128, 0]]);
+
+        let chunk = Chunk::new(ChunkType::PLTE, vec![7; MAX_PALETTE_ENTRIES * 3]);
+        assert_eq!(parse_palette(&chunk).unwrap().len(), MAX_PALETTE_ENTRIES);
+    }
+
+    #[test]
+    fn test_parse_palette_invalid_length() {
+        for len in [0, 4, MAX_PALETTE_ENTRIES * 3 + 3] {
+            let chunk = Chunk::new(ChunkType::PLTE, vec![0; len]);
+            assert!(matches!(
+                parse_palette(&chunk),
+                Err(Error::Palette(PaletteError::InvalidLength(l))) if l == len
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_palette_wrong_type() {
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), vec![0, 0, 0]);
+        assert!(matches!(
+            parse_palette(&chunk),
+            Err(Error::Palette(PaletteError::WrongChunkType(_)))
+        ));
+    }
+}
@@ -0,0 +1,334 @@
+//! Encoding of hidden message payloads.
+//!
+//! A payload is stored as-is unless it was transformed on the way in, in which case the
+//! chunk data starts with a marker naming the transformation. Markers begin with a NUL
+//! byte, which text messages never do; a binary message that happens to start with one
+//! is refused by [`check_plain`] unless it is compressed, as it would be misread.
+//!
+//! Encryption (behind the `encryption` feature) is applied last, so an encrypted payload
+//! may itself be compressed once decrypted.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::chunk::Chunk;
+
+/// Prefix marking a raw-deflate compressed payload.
+const DEFLATE_MARKER: &[u8] = b"\0pngme:deflate\0";
+
+/// Prefix marking a ChaCha20-Poly1305 encrypted payload, followed by the key derivation
+/// salt, the nonce, then the ciphertext.
+const ENCRYPTED_MARKER: &[u8] = b"\0pngme:chacha20poly1305\0";
+
+/// Largest size compressed chunk data is inflated to: the same as the default cap on
+/// chunk data read from a file, so a tiny chunk can't expand without bound.
+pub(crate) const MAX_INFLATED_SIZE: usize = Chunk::DEFAULT_READ_LIMIT as usize;
+
+/// Reads `decoder` to the end, failing with [`io::ErrorKind::InvalidData`] rather than
+/// producing more than [`MAX_INFLATED_SIZE`] bytes.
+pub(crate) fn inflate(decoder: impl Read) -> io::Result<Vec<u8>> {
+    inflate_with_limit(decoder, MAX_INFLATED_SIZE)
+}
+
+fn inflate_with_limit(decoder: impl Read, limit: usize) -> io::Result<Vec<u8>> {
+    let mut inflated = Vec::new();
+    decoder.take(limit as u64 + 1).read_to_end(&mut inflated)?;
+    if inflated.len() > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("decompressed data exceeds {} bytes", limit),
+        ));
+    }
+    Ok(inflated)
+}
+
+/// Deflates `message` and prefixes it with the compression marker.
+pub fn compress(message: &[u8]) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(DEFLATE_MARKER.to_vec(), Compression::best());
+    encoder
+        .write_all(message)
+        .expect("writing to a Vec never fails");
+    encoder.finish().expect("writing to a Vec never fails")
+}
+
+/// Returns true if `data` carries the compression marker.
+pub fn is_compressed(data: &[u8]) -> bool {
+    data.starts_with(DEFLATE_MARKER)
+}
+
+/// Returns true if `data` carries the encryption marker.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENCRYPTED_MARKER)
+}
+
+/// Checks that `message` can be stored untransformed, i.e. doesn't start with a marker
+/// that [`decode`] would mistake for a transformation.
+pub fn check_plain(message: &[u8]) -> Result<(), PayloadError> {
+    if is_compressed(message) || is_encrypted(message) {
+        return Err(PayloadError::MarkerPrefix);
+    }
+    Ok(())
+}
+
+/// Encrypts `message` with a key derived from `passphrase` and prefixes it with the
+/// encryption marker. A fresh salt and nonce are generated on every call.
+#[cfg(feature = "encryption")]
+pub fn encrypt(message: &[u8], passphrase: &str) -> Vec<u8> {
+    use chacha20poly1305::aead::rand_core::RngCore;
+    use chacha20poly1305::aead::{Aead, AeadCore, OsRng};
+    use chacha20poly1305::ChaCha20Poly1305;
+
+    let mut salt = [0; crypto::SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = crypto::cipher(passphrase, &salt)
+        .encrypt(&nonce, message)
+        .expect("encrypting into a Vec never fails");
+
+    [ENCRYPTED_MARKER, &salt, &nonce, &ciphertext].concat()
+}
+
+/// Undoes any transformation applied when the payload was stored.
+///
+/// Encrypted payloads need `passphrase`; it is ignored otherwise. A build without the
+/// `encryption` feature rejects them with [`PayloadError::EncryptionUnsupported`]
+/// whether or not one is given. Data without a marker is returned unchanged.
+pub fn decode(data: &[u8], passphrase: Option<&str>) -> Result<Vec<u8>, PayloadError> {
+    let data = match data.strip_prefix(ENCRYPTED_MARKER) {
+        Some(sealed) => decrypt(sealed, passphrase)?,
+        None => data.to_vec(),
+    };
+
+    match data.strip_prefix(DEFLATE_MARKER) {
+        Some(compressed) => {
+            inflate(DeflateDecoder::new(compressed)).map_err(PayloadError::Decompress)
+        }
+        None => Ok(data),
+    }
+}
+
+#[cfg(feature = "encryption")]
+fn decrypt(sealed: &[u8], passphrase: Option<&str>) -> Result<Vec<u8>, PayloadError> {
+    use chacha20poly1305::aead::Aead;
+    use chacha20poly1305::Nonce;
+
+    let passphrase = passphrase.ok_or(PayloadError::PassphraseRequired)?;
+    if sealed.len() < crypto::SALT_LEN + crypto::NONCE_LEN {
+        return Err(PayloadError::DecryptionFailed);
+    }
+    let (salt, rest) = sealed.split_at(crypto::SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(crypto::NONCE_LEN);
+    crypto::cipher(passphrase, salt)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| PayloadError::DecryptionFailed)
+}
+
+/// Without the feature no passphrase can be given, so asking for one would only mislead.
+#[cfg(not(feature = "encryption"))]
+fn decrypt(_sealed: &[u8], _passphrase: Option<&str>) -> Result<Vec<u8>, PayloadError> {
+    Err(PayloadError::EncryptionUnsupported)
+}
+
+#[cfg(feature = "encryption")]
+mod crypto {
+    use argon2::Argon2;
+    use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit};
+
+    pub const SALT_LEN: usize = 16;
+    pub const NONCE_LEN: usize = 12;
+
+    /// Derives a key from the passphrase with Argon2id and builds the cipher for it.
+    pub fn cipher(passphrase: &str, salt: &[u8]) -> ChaCha20Poly1305 {
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .expect("salt and key lengths are within Argon2's limits");
+        ChaCha20Poly1305::new(&key)
+    }
+}
+
+/// Errors produced when decoding a stored payload.
+#[derive(Debug)]
+pub enum PayloadError {
+    /// The payload is marked as compressed but could not be inflated.
+    Decompress(io::Error),
+    /// The payload is encrypted and no passphrase was given.
+    PassphraseRequired,
+    /// Decryption failed: the passphrase is wrong or the data was tampered with.
+    DecryptionFailed,
+    /// The payload is encrypted but this build lacks the `encryption` feature.
+    EncryptionUnsupported,
+    /// The message to store starts with a payload marker, so would be misread unless
+    /// compressed.
+    MarkerPrefix,
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadError::Decompress(e) => write!(f, "Failed to decompress message: {}", e),
+            PayloadError::PassphraseRequired => {
+                write!(f, "Message is encrypted; a passphrase is required")
+            }
+            PayloadError::DecryptionFailed => {
+                write!(
+                    f,
+                    "Failed to decrypt message: wrong passphrase or corrupted data"
+                )
+            }
+            PayloadError::EncryptionUnsupported => write!(
+                f,
+                "Message is encrypted but pngme was built without the encryption feature"
+            ),
+            PayloadError::MarkerPrefix => write!(
+                f,
+                "Message starts with a pngme payload marker and would be misread; \
+                 store it with --compress"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PayloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PayloadError::Decompress(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inflate_with_limit() {
+        let data = compress(&[0; 1000]);
+        let compressed = &data[DEFLATE_MARKER.len()..];
+        assert_eq!(
+            inflate_with_limit(DeflateDecoder::new(compressed), 1000).unwrap(),
+            [0; 1000]
+        );
+        let err = inflate_with_limit(DeflateDecoder::new(compressed), 999).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let message = "all work and no play makes jack a dull boy. ".repeat(100);
+        let data = compress(message.as_bytes());
+
+        assert!(is_compressed(&data));
+        assert!(data.len() < message.len() / 10);
+        assert_eq!(decode(&data, None).unwrap(), message.as_bytes());
+    }
+
+    #[test]
+    fn test_compress_empty() {
+        let data = compress(b"");
+        assert_eq!(decode(&data, None).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_plain_payload_is_unchanged() {
+        assert!(!is_compressed(b"secret"));
+        assert_eq!(decode(b"secret", None).unwrap(), b"secret");
+    }
+
+    #[test]
+    fn test_corrupt_compressed_payload() {
+        let mut data = DEFLATE_MARKER.to_vec();
+        data.extend_from_slice(&[0xff; 8]);
+        assert!(matches!(
+            decode(&data, None),
+            Err(PayloadError::Decompress(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_plain() {
+        assert!(check_plain(b"secret").is_ok());
+        assert!(check_plain(b"\0secret").is_ok());
+        assert!(matches!(
+            check_plain(&compress(b"secret")),
+            Err(PayloadError::MarkerPrefix)
+        ));
+        assert!(matches!(
+            check_plain(ENCRYPTED_MARKER),
+            Err(PayloadError::MarkerPrefix)
+        ));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_payload_needs_passphrase() {
+        let mut data = ENCRYPTED_MARKER.to_vec();
+        data.extend_from_slice(&[0; 40]);
+        assert!(is_encrypted(&data));
+        assert!(matches!(
+            decode(&data, None),
+            Err(PayloadError::PassphraseRequired)
+        ));
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    #[test]
+    fn test_encrypted_payload_without_feature() {
+        let mut data = ENCRYPTED_MARKER.to_vec();
+        data.extend_from_slice(&[0; 40]);
+        assert!(matches!(
+            decode(&data, None),
+            Err(PayloadError::EncryptionUnsupported)
+        ));
+        assert!(matches!(
+            decode(&data, Some("hunter2")),
+            Err(PayloadError::EncryptionUnsupported)
+        ));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypt_round_trip() {
+        let data = encrypt(b"secret", "hunter2");
+        assert!(is_encrypted(&data));
+        assert!(!data.windows(6).any(|w| w == b"secret"));
+        assert_eq!(decode(&data, Some("hunter2")).unwrap(), b"secret");
+        // Encrypting the same message twice gives different output.
+        assert_ne!(encrypt(b"secret", "hunter2"), data);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypt_wrong_passphrase() {
+        let data = encrypt(b"secret", "hunter2");
+        assert!(matches!(
+            decode(&data, Some("hunter3")),
+            Err(PayloadError::DecryptionFailed)
+        ));
+
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            decode(&tampered, Some("hunter2")),
+            Err(PayloadError::DecryptionFailed)
+        ));
+
+        assert!(matches!(
+            decode(&data[..data.len() - 30], Some("hunter2")),
+            Err(PayloadError::DecryptionFailed)
+        ));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypt_compressed() {
+        let message = "la ".repeat(1000);
+        let data = encrypt(&compress(message.as_bytes()), "hunter2");
+        assert_eq!(decode(&data, Some("hunter2")).unwrap(), message.as_bytes());
+    }
+}
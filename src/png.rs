@@ -0,0 +1,2212 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::chunk::{Chunk, ChunkError};
+use crate::chunk_type::ChunkType;
+use crate::ihdr::{ColorType, IhdrError, ImageHeader};
+
+/// A PNG file: the standard signature followed by an ordered list of chunks, and
+/// possibly bytes after `IEND` kept by [`PngReadOptions::keep_trailing_data`].
+///
+/// `Png::default()` has no chunks at all, so it is not a valid image until at least
+/// `IHDR`, `IDAT` and `IEND` are added with [`Png::append_chunk`] or
+/// [`Png::insert_chunk`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Png {
+    chunks: Vec<Chunk>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    trailing: Vec<u8>,
+}
+
+impl Png {
+    /// The eight-byte signature every PNG file starts with.
+    pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+    /// Creates a PNG from a list of chunks. No validation is performed.
+    pub fn from_chunks(chunks: Vec<Chunk>) -> Png {
+        Png {
+            chunks,
+            trailing: Vec::new(),
+        }
+    }
+
+    /// Reads and parses the PNG file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Png, PngError> {
+        let bytes = fs::read(path)?;
+        Png::try_from(bytes.as_ref())
+    }
+
+    /// Parses a PNG from a reader one chunk at a time, without first buffering the
+    /// whole stream. Applies the same checks as `TryFrom<&[u8]>`, using the default
+    /// [`PngReadOptions`].
+    pub fn from_reader<R: Read>(reader: R) -> Result<Png, PngError> {
+        Self::from_reader_with_options(reader, &PngReadOptions::default())
+    }
+
+    /// Like [`Png::from_reader`], with explicit limits.
+    pub fn from_reader_with_options<R: Read>(
+        mut reader: R,
+        options: &PngReadOptions,
+    ) -> Result<Png, PngError> {
+        let mut chunks = Vec::new();
+        let trailing = Self::read_chunks(&mut reader, options, |chunk| {
+            chunks.push(chunk);
+            ControlFlow::Continue(())
+        })?;
+        Ok(Png { chunks, trailing })
+    }
+
+    /// Parses a PNG from a reader and hands each chunk to `f` as soon as it is read,
+    /// without keeping any of them. Returning [`ControlFlow::Break`] stops reading
+    /// there, so e.g. finding the first `tEXt` chunk doesn't parse the rest of the file.
+    ///
+    /// Applies the same checks as [`Png::from_reader`] to every chunk it reaches; an
+    /// error part way through comes after `f` has seen the chunks before it.
+    pub fn visit_chunks<R: Read, F: FnMut(&Chunk) -> ControlFlow<()>>(
+        mut reader: R,
+        mut f: F,
+    ) -> Result<(), PngError> {
+        Self::read_chunks(&mut reader, &PngReadOptions::default(), |chunk| f(&chunk))?;
+        Ok(())
+    }
+
+    /// Reads the signature and then chunks up to `IEND`, passing each to `f` until it
+    /// breaks. Returns the bytes after `IEND` if `options` keeps them.
+    fn read_chunks<R: Read>(
+        mut reader: R,
+        options: &PngReadOptions,
+        mut f: impl FnMut(Chunk) -> ControlFlow<()>,
+    ) -> Result<Vec<u8>, PngError> {
+        if !has_png_signature(&mut reader)? {
+            return Err(PngError::InvalidHeader);
+        }
+
+        let mut trailing = Vec::new();
+        let mut offset = Self::STANDARD_HEADER.len();
+        let mut length = [0; Chunk::LENGTH_SIZE];
+        while read_exact_or_eof(&mut reader, &mut length, offset)? {
+            // The length has already been consumed to detect EOF, so feed it back in.
+            let chunk = Chunk::read_with_options(
+                &mut (&length[..]).chain(reader.by_ref()),
+                options.max_chunk_size,
+                options.verify_crcs,
+            )
+            .map_err(|e| PngError::at_offset(offset, e))?;
+            offset += Chunk::METADATA_SIZE + chunk.data().len();
+            let is_end = *chunk.chunk_type() == ChunkType::IEND;
+            if f(chunk).is_break() {
+                break;
+            }
+            if is_end {
+                if options.keep_trailing_data {
+                    reader.read_to_end(&mut trailing)?;
+                } else {
+                    let len = io::copy(&mut reader, &mut io::sink())?;
+                    if len > 0 {
+                        return Err(PngError::TrailingData(len as usize));
+                    }
+                }
+                break;
+            }
+        }
+        Ok(trailing)
+    }
+
+    /// Parses as much of a damaged PNG as possible, returning the chunks that parsed
+    /// along with every problem found. A recovery complement to the strict
+    /// `TryFrom<&[u8]>`.
+    ///
+    /// A bad signature is reported and the chunks are parsed anyway. A chunk whose CRC
+    /// doesn't match is reported and left out, since its length is still known. Parsing
+    /// stops at a chunk that is truncated or otherwise unreadable, and at any data after
+    /// `IEND`.
+    pub fn from_bytes_lenient(bytes: &[u8]) -> (Png, Vec<PngError>) {
+        let mut errors = Vec::new();
+        if !is_png(bytes) {
+            errors.push(PngError::InvalidHeader);
+        }
+
+        let mut offset = Self::STANDARD_HEADER.len();
+        let mut rest = bytes.get(offset..).unwrap_or_default();
+        let mut chunks = Vec::new();
+        while !rest.is_empty() {
+            let length = match Chunk::try_from(rest) {
+                Ok(chunk) => {
+                    let length = chunk.data().len();
+                    chunks.push(chunk);
+                    length
+                }
+                Err(e @ ChunkError::CrcMismatch { .. }) => {
+                    errors.push(PngError::at_offset(offset, e));
+                    u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize
+                }
+                Err(e) => {
+                    errors.push(PngError::at_offset(offset, e));
+                    break;
+                }
+            };
+            let is_end = rest[Chunk::LENGTH_SIZE..Chunk::LENGTH_SIZE + Chunk::TYPE_SIZE]
+                == ChunkType::IEND.bytes();
+            rest = &rest[Chunk::METADATA_SIZE + length..];
+            offset += Chunk::METADATA_SIZE + length;
+            if is_end && !rest.is_empty() {
+                errors.push(PngError::TrailingData(rest.len()));
+                break;
+            }
+        }
+        (Png::from_chunks(chunks), errors)
+    }
+
+    /// Writes the serialized PNG to `path`, replacing any existing file.
+    ///
+    /// The bytes are first written to a temporary file next to `path`, which is then
+    /// renamed over it, so a failed write never leaves a half-written file behind.
+    /// If `path` is a symlink the file it points to is replaced instead, and an existing
+    /// file's permissions carry over to the new one.
+    pub fn write_file(&self, path: impl AsRef<Path>) -> Result<(), PngError> {
+        let path = fs::canonicalize(&path).unwrap_or_else(|_| path.as_ref().to_path_buf());
+        let permissions = fs::metadata(&path)
+            .ok()
+            .map(|metadata| metadata.permissions());
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".pngme-tmp");
+
+        let result = self
+            .write_new_file(Path::new(&tmp_path))
+            .and_then(|()| match permissions {
+                Some(permissions) => fs::set_permissions(&tmp_path, permissions),
+                None => Ok(()),
+            })
+            .and_then(|()| fs::rename(&tmp_path, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        Ok(result?)
+    }
+
+    /// Creates or truncates the file at `path` and streams the PNG into it.
+    fn write_new_file(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        self.write_bytes(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the serialized PNG to `writer`: the standard header, then each chunk in
+    /// turn. Unlike [`Png::as_bytes`] the whole file is never buffered in memory.
+    ///
+    /// Writes go straight through, so wrap unbuffered writers in a [`BufWriter`].
+    pub fn to_writer<W: Write>(&self, writer: &mut W) -> Result<(), PngError> {
+        Ok(self.write_bytes(writer)?)
+    }
+
+    fn write_bytes<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&Self::STANDARD_HEADER)?;
+        for chunk in &self.chunks {
+            writer.write_all(&chunk.length().to_be_bytes())?;
+            writer.write_all(&chunk.chunk_type().bytes())?;
+            writer.write_all(chunk.data())?;
+            writer.write_all(&chunk.crc().to_be_bytes())?;
+        }
+        writer.write_all(&self.trailing)
+    }
+
+    /// Appends a chunk at the very end, after any existing `IEND` chunk.
+    pub fn append_chunk(&mut self, chunk: Chunk) {
+        self.chunks.push(chunk);
+    }
+
+    /// Inserts a chunk just before the trailing `IEND` chunk, so the file stays valid.
+    ///
+    /// Fails with [`PngError::MissingIend`] if the last chunk is not `IEND`.
+    pub fn add_chunk_before_iend(&mut self, chunk: Chunk) -> Result<(), PngError> {
+        match self.chunks.last() {
+            Some(last) if *last.chunk_type() == ChunkType::IEND => {
+                self.chunks.insert(self.chunks.len() - 1, chunk);
+                Ok(())
+            }
+            _ => Err(PngError::MissingIend),
+        }
+    }
+
+    /// Inserts a chunk at `index`, shifting later chunks back.
+    ///
+    /// `index` may equal the number of chunks, which appends.
+    pub fn insert_chunk(&mut self, index: usize, chunk: Chunk) -> Result<(), PngError> {
+        if index > self.chunks.len() {
+            return Err(PngError::IndexOutOfRange {
+                index,
+                len: self.chunks.len(),
+            });
+        }
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+
+    /// Removes and returns the first chunk of the given type.
+    pub fn remove_first_chunk(&mut self, chunk_type: &str) -> Result<Chunk, PngError> {
+        let index = self
+            .index_of(chunk_type)
+            .ok_or_else(|| PngError::ChunkNotFound(chunk_type.to_string()))?;
+        Ok(self.chunks.remove(index))
+    }
+
+    /// Replaces the data of the first chunk of the given type, recomputing its CRC.
+    pub fn replace_chunk_data(&mut self, chunk_type: &str, data: Vec<u8>) -> crate::Result<()> {
+        let chunk = self
+            .chunks
+            .iter_mut()
+            .find(|chunk| is_type(chunk, chunk_type))
+            .ok_or_else(|| PngError::ChunkNotFound(chunk_type.to_string()))?;
+        chunk.set_data(data);
+        Ok(())
+    }
+
+    /// Removes the first chunk of each of the given types, returning one result per type
+    /// in the same order. A type listed twice removes two chunks.
+    pub fn remove_first_chunks(&mut self, chunk_types: &[&str]) -> Vec<crate::Result<Chunk>> {
+        chunk_types
+            .iter()
+            .map(|chunk_type| Ok(self.remove_first_chunk(chunk_type)?))
+            .collect()
+    }
+
+    pub fn chunks(&self) -> &[Chunk] {
+        &self.chunks
+    }
+
+    /// The number of chunks.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Whether the PNG has no chunks at all, as a [`Png::default`] does.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Iterates over the chunks in file order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Chunk> {
+        self.chunks.iter()
+    }
+
+    /// Returns the first chunk of the given type, if any.
+    pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
+        self.chunks.iter().find(|chunk| is_type(chunk, chunk_type))
+    }
+
+    /// Returns every chunk of the given type, in file order.
+    pub fn chunks_by_type(&self, chunk_type: &str) -> Vec<&Chunk> {
+        self.chunks
+            .iter()
+            .filter(|chunk| is_type(chunk, chunk_type))
+            .collect()
+    }
+
+    /// Returns the index of the first chunk of the given type, if any.
+    pub fn index_of(&self, chunk_type: &str) -> Option<usize> {
+        self.chunks
+            .iter()
+            .position(|chunk| is_type(chunk, chunk_type))
+    }
+
+    /// Returns the index of the last chunk of the given type, if any.
+    pub fn last_index_of(&self, chunk_type: &str) -> Option<usize> {
+        self.chunks
+            .iter()
+            .rposition(|chunk| is_type(chunk, chunk_type))
+    }
+
+    /// Like [`Png::chunk_by_type`], but compares the letters case-insensitively, so
+    /// `rust` finds a `ruSt` chunk.
+    ///
+    /// The case of each letter carries the chunk's property bits, so this deliberately
+    /// ignores them; it is a convenience for searching, not a semantic comparison.
+    pub fn chunk_by_type_ignore_case(&self, chunk_type: &str) -> Option<&Chunk> {
+        self.chunks
+            .iter()
+            .find(|chunk| is_type_ignore_case(chunk, chunk_type))
+    }
+
+    /// Like [`Png::chunks_by_type`], but compares the letters case-insensitively as
+    /// [`Png::chunk_by_type_ignore_case`] does.
+    pub fn chunks_by_type_ignore_case(&self, chunk_type: &str) -> Vec<&Chunk> {
+        self.chunks
+            .iter()
+            .filter(|chunk| is_type_ignore_case(chunk, chunk_type))
+            .collect()
+    }
+
+    /// Finds every occurrence of `needle` in the chunk data, returning the chunk index,
+    /// its type and the byte offset of the match within the data. Overlapping matches are
+    /// all reported; an empty needle matches nothing.
+    pub fn search(&self, needle: &[u8]) -> Vec<(usize, ChunkType, usize)> {
+        if needle.is_empty() {
+            return Vec::new();
+        }
+        let mut matches = Vec::new();
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            for (offset, window) in chunk.data().windows(needle.len()).enumerate() {
+                if window == needle {
+                    matches.push((index, *chunk.chunk_type(), offset));
+                }
+            }
+        }
+        matches
+    }
+
+    /// Returns the data of every chunk of the given type as a string, in file order.
+    ///
+    /// A chunk whose data isn't valid UTF-8 gives an error in its place without affecting
+    /// the others. Payloads are returned as stored; see [`crate::payload::decode`] for
+    /// compressed or encrypted ones.
+    pub fn extract_messages(&self, chunk_type: &str) -> Vec<crate::Result<String>> {
+        self.chunks_by_type(chunk_type)
+            .into_iter()
+            .map(|chunk| Ok(chunk.data_as_string()?))
+            .collect()
+    }
+
+    /// Compares the chunks of two PNGs, going from `self` to `other`.
+    ///
+    /// Chunks are matched by type and occurrence: the second `ruSt` chunk in one file is
+    /// compared with the second `ruSt` chunk in the other, wherever they sit in the file.
+    pub fn diff(&self, other: &Png) -> PngDiff {
+        let ours = occurrences(&self.chunks);
+        let theirs = occurrences(&other.chunks);
+        let lookup: HashMap<_, _> = theirs.iter().map(|(key, chunk)| (*key, *chunk)).collect();
+        let mut diff = PngDiff::default();
+
+        for (key, chunk) in &ours {
+            match lookup.get(key) {
+                None => diff.removed.push(*key),
+                Some(other) if other.data() != chunk.data() => diff.modified.push(*key),
+                Some(_) => {}
+            }
+        }
+        let ours: HashMap<_, _> = ours.into_iter().collect();
+        for (key, _) in theirs {
+            if !ours.contains_key(&key) {
+                diff.added.push(key);
+            }
+        }
+        diff
+    }
+
+    /// Checks that the chunk list starts with `IHDR` and ends with `IEND`.
+    ///
+    /// Parsing does not enforce this, so call it to catch truncated or malformed files.
+    pub fn validate(&self) -> Result<(), PngError> {
+        match self.chunks.first() {
+            Some(chunk) if *chunk.chunk_type() == ChunkType::IHDR => {}
+            _ => return Err(PngError::MissingIhdr),
+        }
+        match self.chunks.last() {
+            Some(chunk) if *chunk.chunk_type() == ChunkType::IEND => Ok(()),
+            _ => Err(PngError::MissingIend),
+        }
+    }
+
+    /// Width and height of the image in pixels, read from the first `IHDR` chunk.
+    ///
+    /// Fails with [`PngError::MissingIhdr`] if there is no `IHDR` chunk, or
+    /// [`PngError::Ihdr`] if it is malformed.
+    pub fn dimensions(&self) -> crate::Result<(u32, u32)> {
+        let header = self.image_header()?;
+        Ok((header.width, header.height))
+    }
+
+    /// Parses the first `IHDR` chunk.
+    fn image_header(&self) -> Result<ImageHeader, PngError> {
+        let chunk = self.chunk_by_type("IHDR").ok_or(PngError::MissingIhdr)?;
+        Ok(ImageHeader::parse(chunk)?)
+    }
+
+    /// Checks the presence of `PLTE` against the `IHDR` color type: a palette image must
+    /// have one, and a grayscale image must not.
+    ///
+    /// Fails with [`PngError::MissingIhdr`] if there is no `IHDR` chunk, or
+    /// [`PngError::Ihdr`] if it is malformed.
+    pub fn validate_palette(&self) -> crate::Result<()> {
+        let color_type = self.image_header()?.color_type;
+        let has_palette = self.chunk_by_type("PLTE").is_some();
+        match color_type {
+            ColorType::Palette if !has_palette => Err(PngError::PaletteRequired.into()),
+            ColorType::Grayscale | ColorType::GrayscaleAlpha if has_palette => {
+                Err(PngError::PaletteForbidden(color_type).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Lists the standard critical chunks a valid PNG needs but this one lacks, in the
+    /// order they would appear: `IHDR`, `PLTE`, `IDAT` and `IEND`.
+    ///
+    /// `PLTE` is only required when the `IHDR` color type is [`ColorType::Palette`], so
+    /// it is never reported if the `IHDR` chunk is missing or malformed. Unlike
+    /// [`Png::validate`], only presence is checked, not position.
+    pub fn missing_critical_chunks(&self) -> Vec<ChunkType> {
+        let needs_palette = self
+            .chunk_by_type("IHDR")
+            .and_then(|chunk| ImageHeader::from_chunk(chunk).ok())
+            .is_some_and(|header| header.color_type == ColorType::Palette);
+        let mut required = vec![ChunkType::IHDR];
+        if needs_palette {
+            required.push(ChunkType::PLTE);
+        }
+        required.extend([ChunkType::IDAT, ChunkType::IEND]);
+
+        required
+            .into_iter()
+            .filter(|required| !self.chunks.iter().any(|c| c.chunk_type() == required))
+            .collect()
+    }
+
+    /// Like [`Png::validate`], but reports every failed check rather than only the first.
+    ///
+    /// A parsed PNG has already had its signature and CRCs checked; use [`verify_bytes`]
+    /// to check those on raw bytes as well.
+    pub fn verify(&self) -> Result<(), Vec<PngError>> {
+        let chunk_types: Vec<ChunkType> = self.chunks.iter().map(|c| *c.chunk_type()).collect();
+        let errors = check_order(&chunk_types);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Roughly how many more message bytes could be hidden in new ancillary chunks before
+    /// the file outgrows `limits.max_file_size`. A soft guide, not a guarantee.
+    ///
+    /// Assumes the messages are stored as is, with no compression or encryption, and
+    /// split into chunks of at most `limits.max_chunk_size` bytes, each costing
+    /// [`Chunk::METADATA_SIZE`] bytes on top of its data.
+    pub fn hidden_capacity_remaining(&self, limits: &CapacityLimits) -> usize {
+        let free = limits.max_file_size.saturating_sub(self.byte_size());
+        let max_chunk_size = (limits.max_chunk_size as usize).max(1);
+        let full_chunk = max_chunk_size + Chunk::METADATA_SIZE;
+        let last_chunk = (free % full_chunk).saturating_sub(Chunk::METADATA_SIZE);
+        free / full_chunk * max_chunk_size + last_chunk
+    }
+
+    /// Size in bytes of the serialized PNG, computed without serializing it.
+    pub fn byte_size(&self) -> usize {
+        Self::STANDARD_HEADER.len()
+            + self
+                .chunks
+                .iter()
+                .map(|chunk| Chunk::METADATA_SIZE + chunk.data().len())
+                .sum::<usize>()
+            + self.trailing.len()
+    }
+
+    /// Bytes that followed `IEND` in the input, kept when reading with
+    /// [`PngReadOptions::keep_trailing_data`] and written back after the last chunk.
+    /// Empty otherwise.
+    pub fn trailing_bytes(&self) -> &[u8] {
+        &self.trailing
+    }
+
+    /// Returns the ancillary chunks (lowercase first letter), in file order.
+    pub fn ancillary_chunks(&self) -> Vec<&Chunk> {
+        self.chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type().is_ancillary())
+            .collect()
+    }
+
+    /// Returns the critical chunks (uppercase first letter), in file order.
+    pub fn critical_chunks(&self) -> Vec<&Chunk> {
+        self.chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type().is_critical())
+            .collect()
+    }
+
+    /// Removes every ancillary chunk, keeping the critical ones in their original order.
+    /// Returns how many chunks were removed.
+    pub fn strip_ancillary_chunks(&mut self) -> usize {
+        let before = self.chunks.len();
+        self.chunks.retain(|chunk| chunk.chunk_type().is_critical());
+        before - self.chunks.len()
+    }
+
+    /// Removes every chunk with the same type and data as an earlier one, keeping the
+    /// first occurrence, and returns how many were removed. Stored CRCs are ignored, so
+    /// a copy with a stale CRC counts as a duplicate too.
+    pub fn dedup_chunks(&mut self) -> usize {
+        let before = self.chunks.len();
+        let mut kept: Vec<Chunk> = Vec::with_capacity(before);
+        for chunk in self.chunks.drain(..) {
+            if !kept.contains(&chunk) {
+                kept.push(chunk);
+            }
+        }
+        self.chunks = kept;
+        before - self.chunks.len()
+    }
+
+    /// Fixes every chunk whose stored CRC doesn't match its type and data, and returns
+    /// how many were fixed. Only a PNG read without
+    /// [`PngReadOptions::verify_crcs`] can have such chunks.
+    pub fn recompute_crcs(&mut self) -> usize {
+        let mut fixed = 0;
+        for chunk in self.chunks.iter_mut().filter(|chunk| !chunk.crc_is_valid()) {
+            chunk.recompute_crc();
+            fixed += 1;
+        }
+        fixed
+    }
+
+    /// Changes the type of every chunk of type `from` to `to`, keeping the data, and
+    /// returns how many were changed. Each CRC is recomputed, since it covers the type.
+    ///
+    /// Fails without changing anything if `to` is not a valid chunk type.
+    pub fn rename_chunk_type(&mut self, from: &str, to: &str) -> crate::Result<usize> {
+        let to = ChunkType::from_str(to)?;
+        let mut renamed = 0;
+        for chunk in self.chunks.iter_mut().filter(|chunk| is_type(chunk, from)) {
+            chunk.set_chunk_type(to);
+            renamed += 1;
+        }
+        Ok(renamed)
+    }
+
+    /// Serializes the PNG: the standard header followed by every chunk in order.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        Self::STANDARD_HEADER
+            .iter()
+            .copied()
+            .chain(self.chunks.iter().flat_map(Chunk::as_bytes))
+            .chain(self.trailing.iter().copied())
+            .collect()
+    }
+}
+
+/// Builds a [`Png`] from scratch, filling in the `IHDR` and `IEND` chunks.
+#[derive(Debug, Clone, Default)]
+pub struct PngBuilder {
+    header: Option<ImageHeader>,
+    chunks: Vec<Chunk>,
+    allow_missing_idat: bool,
+}
+
+impl PngBuilder {
+    pub fn new() -> PngBuilder {
+        PngBuilder::default()
+    }
+
+    /// Sets the image header, which becomes the leading `IHDR` chunk.
+    pub fn header(mut self, header: ImageHeader) -> PngBuilder {
+        self.header = Some(header);
+        self
+    }
+
+    /// Appends a chunk after the ones already added.
+    pub fn add_chunk(mut self, chunk: Chunk) -> PngBuilder {
+        self.chunks.push(chunk);
+        self
+    }
+
+    /// Lets [`PngBuilder::build`] succeed without an `IDAT` chunk, e.g. for test
+    /// fixtures that only exercise ancillary chunks.
+    pub fn allow_missing_idat(mut self, allow: bool) -> PngBuilder {
+        self.allow_missing_idat = allow;
+        self
+    }
+
+    /// Assembles the PNG.
+    ///
+    /// The header set with [`PngBuilder::header`] is inserted first; without one, the
+    /// first added chunk must be `IHDR`, otherwise this fails with
+    /// [`PngError::MissingIhdr`]. Any further `IHDR`, such as one added alongside a
+    /// header, fails with [`PngError::DuplicateIhdr`]. An `IEND` chunk is appended unless
+    /// the last added chunk already is one; an `IEND` anywhere else fails with
+    /// [`PngError::MisplacedIend`]. Fails with [`PngError::MissingIdat`] if no `IDAT`
+    /// chunk was added, unless that was allowed.
+    pub fn build(self) -> crate::Result<Png> {
+        let mut chunks = Vec::with_capacity(self.chunks.len() + 2);
+        if let Some(header) = self.header {
+            chunks.push(header.to_chunk());
+        }
+        chunks.extend(self.chunks);
+
+        if chunks.first().map(Chunk::chunk_type) != Some(&ChunkType::IHDR) {
+            return Err(PngError::MissingIhdr.into());
+        }
+        if chunks[1..]
+            .iter()
+            .any(|chunk| *chunk.chunk_type() == ChunkType::IHDR)
+        {
+            return Err(PngError::DuplicateIhdr.into());
+        }
+        if chunks[..chunks.len() - 1]
+            .iter()
+            .any(|chunk| *chunk.chunk_type() == ChunkType::IEND)
+        {
+            return Err(PngError::MisplacedIend.into());
+        }
+        if !self.allow_missing_idat
+            && !chunks
+                .iter()
+                .any(|chunk| *chunk.chunk_type() == ChunkType::IDAT)
+        {
+            return Err(PngError::MissingIdat.into());
+        }
+        if chunks.last().map(Chunk::chunk_type) != Some(&ChunkType::IEND) {
+            chunks.push(Chunk::new(ChunkType::IEND, Vec::new()));
+        }
+        Ok(Png::from_chunks(chunks))
+    }
+}
+
+/// The differences between two PNGs, as returned by [`Png::diff`].
+///
+/// Each entry is a chunk type and its occurrence index among chunks of that type, in the
+/// order the chunks appear in their file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PngDiff {
+    /// Chunks only in the second PNG.
+    pub added: Vec<(ChunkType, usize)>,
+    /// Chunks only in the first PNG.
+    pub removed: Vec<(ChunkType, usize)>,
+    /// Chunks in both whose data differs.
+    pub modified: Vec<(ChunkType, usize)>,
+}
+
+impl PngDiff {
+    /// Returns true if the two PNGs have the same chunks with the same data.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Pairs each chunk with its type and how many chunks of that type came before it.
+fn occurrences(chunks: &[Chunk]) -> Vec<((ChunkType, usize), &Chunk)> {
+    let mut seen: HashMap<ChunkType, usize> = HashMap::new();
+    chunks
+        .iter()
+        .map(|chunk| {
+            let count = seen.entry(*chunk.chunk_type()).or_insert(0);
+            let key = (*chunk.chunk_type(), *count);
+            *count += 1;
+            (key, chunk)
+        })
+        .collect()
+}
+
+/// Options for [`Png::from_reader_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PngReadOptions {
+    /// Largest chunk data length to accept, bounding how much a single chunk can make
+    /// the reader allocate. Defaults to [`Chunk::DEFAULT_READ_LIMIT`] (64 MiB).
+    pub max_chunk_size: u32,
+    /// Keep any bytes after `IEND` as [`Png::trailing_bytes`] so they survive a round
+    /// trip, as some tools hide data there. Off by default, which rejects them with
+    /// [`PngError::TrailingData`].
+    pub keep_trailing_data: bool,
+    /// Check every chunk's stored CRC, rejecting mismatches with
+    /// [`PngError::CrcMismatch`]. On by default; turn it off to load chunks with stale
+    /// CRCs as they are, e.g. for [`Png::recompute_crcs`] to fix.
+    pub verify_crcs: bool,
+}
+
+impl Default for PngReadOptions {
+    fn default() -> Self {
+        PngReadOptions {
+            max_chunk_size: Chunk::DEFAULT_READ_LIMIT,
+            keep_trailing_data: false,
+            verify_crcs: true,
+        }
+    }
+}
+
+/// Limits for [`Png::hidden_capacity_remaining`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityLimits {
+    /// Largest the file should grow, in bytes. Defaults to 16 MiB.
+    pub max_file_size: usize,
+    /// Largest data length for each new chunk. Defaults to [`Chunk::DEFAULT_READ_LIMIT`]
+    /// (64 MiB), so every chunk stays readable with the default [`PngReadOptions`].
+    pub max_chunk_size: u32,
+}
+
+impl Default for CapacityLimits {
+    fn default() -> Self {
+        CapacityLimits {
+            max_file_size: 16 * 1024 * 1024,
+            max_chunk_size: Chunk::DEFAULT_READ_LIMIT,
+        }
+    }
+}
+
+/// Collects chunks into a PNG in iteration order. Like [`Png::from_chunks`], no
+/// validation is performed.
+impl FromIterator<Chunk> for Png {
+    fn from_iter<I: IntoIterator<Item = Chunk>>(iter: I) -> Self {
+        Png::from_chunks(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a Png {
+    type Item = &'a Chunk;
+    type IntoIter = std::slice::Iter<'a, Chunk>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Returns true if `bytes` starts with the PNG signature. Nothing past the first eight
+/// bytes is looked at, so this is a cheap check before a full parse.
+pub fn is_png(bytes: &[u8]) -> bool {
+    bytes.starts_with(&Png::STANDARD_HEADER)
+}
+
+/// Reads eight bytes from `reader` and returns true if they are the PNG signature.
+///
+/// A stream shorter than the signature is not a PNG rather than an error.
+pub fn has_png_signature<R: Read>(mut reader: R) -> io::Result<bool> {
+    let mut header = [0; Png::STANDARD_HEADER.len()];
+    match reader.read_exact(&mut header) {
+        Ok(()) => Ok(header == Png::STANDARD_HEADER),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Checks a serialized PNG without stopping at the first problem: the signature, every
+/// chunk's CRC, anything after `IEND`, and the [`Png::verify`] checks on chunk order.
+///
+/// A chunk whose CRC doesn't match is reported and skipped, since its length is still
+/// known. A chunk that can't be parsed at all ends the scan, as nothing after it can be
+/// located.
+pub fn verify_bytes(bytes: &[u8]) -> Result<(), Vec<PngError>> {
+    let mut errors = Vec::new();
+    if !is_png(bytes) {
+        errors.push(PngError::InvalidHeader);
+    }
+
+    let mut offset = Png::STANDARD_HEADER.len();
+    let mut rest = bytes.get(offset..).unwrap_or_default();
+    let mut chunk_types = Vec::new();
+    while !rest.is_empty() {
+        let length = match Chunk::try_from(rest) {
+            Ok(chunk) => chunk.data().len(),
+            Err(e @ ChunkError::CrcMismatch { .. }) => {
+                errors.push(PngError::at_offset(offset, e));
+                u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize
+            }
+            Err(e) => {
+                errors.push(PngError::at_offset(offset, e));
+                break;
+            }
+        };
+        let type_bytes = &rest[Chunk::LENGTH_SIZE..Chunk::LENGTH_SIZE + Chunk::TYPE_SIZE];
+        let chunk_type = ChunkType::try_from(type_bytes).expect("checked by Chunk::try_from");
+        chunk_types.push(chunk_type);
+        rest = &rest[Chunk::METADATA_SIZE + length..];
+        offset += Chunk::METADATA_SIZE + length;
+        if chunk_type == ChunkType::IEND && !rest.is_empty() {
+            errors.push(PngError::TrailingData(rest.len()));
+            break;
+        }
+    }
+
+    errors.extend(check_order(&chunk_types));
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The [`Png::verify`] checks, over just the chunk types in file order.
+fn check_order(chunk_types: &[ChunkType]) -> Vec<PngError> {
+    let mut errors = Vec::new();
+    if chunk_types.first() != Some(&ChunkType::IHDR) {
+        errors.push(PngError::MissingIhdr);
+    }
+    if chunk_types.last() != Some(&ChunkType::IEND) {
+        errors.push(PngError::MissingIend);
+    }
+    errors
+}
+
+/// Fills `buf` from `reader`, returning `false` if the reader was already at EOF.
+///
+/// Running out of input after filling only part of `buf` is a
+/// [`PngError::TruncatedChunk`] at `offset`.
+fn read_exact_or_eof<R: Read>(
+    reader: &mut R,
+    buf: &mut [u8],
+    offset: usize,
+) -> Result<bool, PngError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(PngError::TruncatedChunk { offset }),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(true)
+}
+
+fn is_type(chunk: &Chunk, chunk_type: &str) -> bool {
+    chunk.chunk_type().bytes() == chunk_type.as_bytes()
+}
+
+fn is_type_ignore_case(chunk: &Chunk, chunk_type: &str) -> bool {
+    chunk
+        .chunk_type()
+        .bytes()
+        .eq_ignore_ascii_case(chunk_type.as_bytes())
+}
+
+/// Parses a PNG, checking the signature and every chunk's CRC.
+///
+/// Bytes left over after the `IEND` chunk are rejected with [`PngError::TrailingData`].
+impl TryFrom<&[u8]> for Png {
+    type Error = PngError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if !is_png(bytes) {
+            return Err(PngError::InvalidHeader);
+        }
+
+        let mut offset = Self::STANDARD_HEADER.len();
+        let mut rest = &bytes[offset..];
+        let mut chunks = Vec::new();
+        while !rest.is_empty() {
+            let chunk = Chunk::try_from(rest).map_err(|e| PngError::at_offset(offset, e))?;
+            let size = Chunk::METADATA_SIZE + chunk.data().len();
+            rest = &rest[size..];
+            offset += size;
+            let is_end = *chunk.chunk_type() == ChunkType::IEND;
+            chunks.push(chunk);
+            if is_end && !rest.is_empty() {
+                return Err(PngError::TrailingData(rest.len()));
+            }
+        }
+        Ok(Png::from_chunks(chunks))
+    }
+}
+
+/// Errors produced when parsing or manipulating a [`Png`].
+#[derive(Debug)]
+pub enum PngError {
+    /// The input does not start with [`Png::STANDARD_HEADER`].
+    InvalidHeader,
+    /// The chunk starting at byte `offset` of the input failed to parse.
+    Chunk { offset: usize, error: ChunkError },
+    /// The stored CRC of the chunk starting at byte `offset` does not match the one
+    /// computed over its type and data. `expected` is the computed value, `actual` the
+    /// one read from the input.
+    CrcMismatch {
+        offset: usize,
+        expected: u32,
+        actual: u32,
+    },
+    /// The input ended part way through the chunk starting at byte `offset`.
+    TruncatedChunk { offset: usize },
+    /// Bytes were found after the `IEND` chunk; carries how many.
+    TrailingData(usize),
+    /// No chunk of the requested type exists.
+    ChunkNotFound(String),
+    /// The first chunk is not `IHDR`, or there is no `IHDR` chunk at all.
+    MissingIhdr,
+    /// The `IHDR` chunk could not be parsed.
+    Ihdr(IhdrError),
+    /// There is more than one `IHDR` chunk.
+    DuplicateIhdr,
+    /// The last chunk is not `IEND`.
+    MissingIend,
+    /// An `IEND` chunk comes before other chunks instead of last.
+    MisplacedIend,
+    /// There is no `IDAT` chunk.
+    MissingIdat,
+    /// The `IHDR` color type is [`ColorType::Palette`] but there is no `PLTE` chunk.
+    PaletteRequired,
+    /// There is a `PLTE` chunk but the `IHDR` color type is grayscale, which doesn't
+    /// allow one; carries the color type.
+    PaletteForbidden(ColorType),
+    /// An insertion index was past the end of the chunk list.
+    IndexOutOfRange { index: usize, len: usize },
+    /// A verification found problems; carries how many.
+    VerificationFailed(usize),
+    /// Reading or writing a file failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PngError::InvalidHeader => write!(f, "Input does not start with the PNG signature"),
+            PngError::Chunk { offset, error } => {
+                write!(f, "Invalid chunk at byte {}: {}", offset, error)
+            }
+            PngError::CrcMismatch {
+                offset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "CRC mismatch in chunk at byte {}: expected {:#010x}, found {:#010x}",
+                offset, expected, actual
+            ),
+            PngError::TruncatedChunk { offset } => {
+                write!(f, "Input ends inside the chunk at byte {}", offset)
+            }
+            PngError::TrailingData(len) => {
+                write!(f, "Found {} unexpected bytes after the IEND chunk", len)
+            }
+            PngError::ChunkNotFound(chunk_type) => {
+                write!(f, "No chunk of type {} found", chunk_type)
+            }
+            PngError::MissingIhdr => write!(f, "PNG does not start with an IHDR chunk"),
+            PngError::DuplicateIhdr => write!(f, "PNG has more than one IHDR chunk"),
+            PngError::MissingIend => write!(f, "PNG does not end with an IEND chunk"),
+            PngError::MisplacedIend => write!(f, "PNG has chunks after an IEND chunk"),
+            PngError::MissingIdat => write!(f, "PNG has no IDAT chunk"),
+            PngError::PaletteRequired => {
+                write!(f, "PNG uses palette colors but has no PLTE chunk")
+            }
+            PngError::PaletteForbidden(color_type) => write!(
+                f,
+                "PNG has a PLTE chunk, which color type {} doesn't allow",
+                color_type
+            ),
+            PngError::Ihdr(e) => write!(f, "Invalid IHDR chunk: {}", e),
+            PngError::IndexOutOfRange { index, len } => write!(
+                f,
+                "Chunk index {} is out of range for a PNG with {} chunks",
+                index, len
+            ),
+            PngError::VerificationFailed(count) => {
+                write!(f, "PNG failed verification with {} problems", count)
+            }
+            PngError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PngError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PngError::Chunk { error, .. } => Some(error),
+            PngError::Ihdr(e) => Some(e),
+            PngError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl PngError {
+    /// Places a chunk parsing error at the byte offset where the chunk starts, using the
+    /// dedicated variants for CRC mismatches and truncation.
+    fn at_offset(offset: usize, error: ChunkError) -> PngError {
+        match error {
+            ChunkError::CrcMismatch { expected, actual } => PngError::CrcMismatch {
+                offset,
+                expected,
+                actual,
+            },
+            ChunkError::TooShort | ChunkError::UnexpectedEof => PngError::TruncatedChunk { offset },
+            ChunkError::Io(e) => PngError::Io(e),
+            error => PngError::Chunk { offset, error },
+        }
+    }
+}
+
+impl From<IhdrError> for PngError {
+    fn from(e: IhdrError) -> Self {
+        PngError::Ihdr(e)
+    }
+}
+
+impl From<io::Error> for PngError {
+    fn from(e: io::Error) -> Self {
+        PngError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkTypeError;
+    use crate::crc::crc32;
+    use crate::Error;
+
+    /// A valid 1x1 grayscale image: IHDR, IDAT, IEND.
+    #[rustfmt::skip]
+    const TINY_PNG: [u8; 67] = [
+        137, 80, 78, 71, 13, 10, 26, 10, // signature
+        0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0, 58, 126, 155, 85, // IHDR
+        0, 0, 0, 10, 73, 68, 65, 84, 120, 156, 99, 96, 0, 0, 0, 2, 0, 1, 72, 175, 164, 113, // IDAT
+        0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130, // IEND
+    ];
+
+    fn chunk_from_strings(chunk_type: &str, data: &str) -> Chunk {
+        let chunk_type = ChunkType::from_str(chunk_type).unwrap();
+        Chunk::new(chunk_type, data.as_bytes().to_vec())
+    }
+
+    fn testing_chunks() -> Vec<Chunk> {
+        vec![
+            chunk_from_strings("FrSt", "I am the first chunk"),
+            chunk_from_strings("miDl", "I am another chunk"),
+            chunk_from_strings("LASt", "I am the last chunk"),
+        ]
+    }
+
+    fn testing_png() -> Png {
+        Png::from_chunks(testing_chunks())
+    }
+
+    #[test]
+    fn test_from_chunks() {
+        let png = testing_png();
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_default_is_empty() {
+        let png = Png::default();
+        assert!(png.chunks().is_empty());
+        assert_eq!(png.as_bytes(), Png::STANDARD_HEADER);
+    }
+
+    #[test]
+    fn test_collect_into_png() {
+        let png: Png = testing_chunks().into_iter().collect();
+        let types: Vec<String> = png
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["FrSt", "miDl", "LASt"]);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let png = testing_png();
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["FrSt", "miDl", "LASt"]);
+    }
+
+    #[test]
+    fn test_append_chunk() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message"));
+        assert_eq!(png.chunks().len(), 4);
+        let last = png.chunks().last().unwrap();
+        assert_eq!(&last.chunk_type().to_string(), "TeSt");
+        assert_eq!(&last.data_as_string().unwrap(), "Message");
+    }
+
+    #[test]
+    fn test_hidden_capacity_remaining() {
+        let mut png = Png::try_from(&TINY_PNG[..]).unwrap();
+        let limits = CapacityLimits::default();
+        let before = png.hidden_capacity_remaining(&limits);
+        assert_eq!(
+            before,
+            limits.max_file_size - TINY_PNG.len() - Chunk::METADATA_SIZE
+        );
+
+        png.add_chunk_before_iend(chunk_from_strings("ruSt", "secret"))
+            .unwrap();
+        assert_eq!(
+            png.hidden_capacity_remaining(&limits),
+            before - 6 - Chunk::METADATA_SIZE
+        );
+    }
+
+    #[test]
+    fn test_hidden_capacity_counts_chunk_overhead() {
+        let png = Png::try_from(&TINY_PNG[..]).unwrap();
+        let limits = |max_file_size| CapacityLimits {
+            max_file_size,
+            max_chunk_size: 10,
+        };
+        // Room for two full chunks of 10 data bytes, then 5 bytes in a third.
+        assert_eq!(
+            png.hidden_capacity_remaining(&limits(TINY_PNG.len() + 61)),
+            25
+        );
+        // Not even room for one chunk's overhead.
+        assert_eq!(
+            png.hidden_capacity_remaining(&limits(TINY_PNG.len() + 12)),
+            0
+        );
+        assert_eq!(png.hidden_capacity_remaining(&limits(10)), 0);
+    }
+
+    #[test]
+    fn test_index_of() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "again"));
+        assert_eq!(png.index_of("FrSt"), Some(0));
+        assert_eq!(png.index_of("miDl"), Some(1));
+        assert_eq!(png.last_index_of("miDl"), Some(3));
+        assert_eq!(png.last_index_of("LASt"), Some(2));
+        assert_eq!(png.index_of("TeSt"), None);
+        assert_eq!(png.last_index_of("TeSt"), None);
+    }
+
+    #[test]
+    fn test_add_chunk_before_iend() {
+        let mut png = Png::try_from(&TINY_PNG[..]).unwrap();
+        png.add_chunk_before_iend(chunk_from_strings("TeSt", "Message"))
+            .unwrap();
+
+        let len = png.chunks().len();
+        assert_eq!(len, 4);
+        assert_eq!(png.chunks()[len - 2], chunk_from_strings("TeSt", "Message"));
+        assert_eq!(*png.chunks()[len - 1].chunk_type(), ChunkType::IEND);
+    }
+
+    #[test]
+    fn test_add_chunk_before_iend_requires_iend() {
+        let mut png = testing_png();
+        assert!(matches!(
+            png.add_chunk_before_iend(chunk_from_strings("TeSt", "Message")),
+            Err(PngError::MissingIend)
+        ));
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_insert_chunk() {
+        let mut png = testing_png();
+        png.insert_chunk(1, chunk_from_strings("TeSt", "Message"))
+            .unwrap();
+        png.insert_chunk(4, chunk_from_strings("EnDs", "Last"))
+            .unwrap();
+
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["FrSt", "TeSt", "miDl", "LASt", "EnDs"]);
+    }
+
+    #[test]
+    fn test_insert_chunk_out_of_range() {
+        let mut png = testing_png();
+        assert!(matches!(
+            png.insert_chunk(4, chunk_from_strings("TeSt", "Message")),
+            Err(PngError::IndexOutOfRange { index: 4, len: 3 })
+        ));
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_remove_first_chunk() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message"));
+        png.append_chunk(chunk_from_strings("TeSt", "Another"));
+
+        let removed = png.remove_first_chunk("TeSt").unwrap();
+        assert_eq!(&removed.data_as_string().unwrap(), "Message");
+        assert_eq!(png.chunks().len(), 4);
+        let last = png.chunks().last().unwrap();
+        assert_eq!(&last.data_as_string().unwrap(), "Another");
+    }
+
+    #[test]
+    fn test_remove_missing_chunk() {
+        let mut png = testing_png();
+        assert!(matches!(
+            png.remove_first_chunk("TeSt"),
+            Err(PngError::ChunkNotFound(chunk_type)) if chunk_type == "TeSt"
+        ));
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_iter() {
+        let png = testing_png();
+        assert_eq!(png.iter().count(), 3);
+
+        let mut count = 0;
+        for chunk in &png {
+            assert_eq!(chunk.chunk_type(), png.chunks()[count].chunk_type());
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_chunk_by_type() {
+        let png = testing_png();
+        let chunk = png.chunk_by_type("FrSt").unwrap();
+        assert_eq!(&chunk.chunk_type().to_string(), "FrSt");
+        assert_eq!(&chunk.data_as_string().unwrap(), "I am the first chunk");
+    }
+
+    #[test]
+    fn test_chunk_by_type_missing() {
+        let png = testing_png();
+        assert!(png.chunk_by_type("TeSt").is_none());
+    }
+
+    #[test]
+    fn test_chunks_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("tEXt", "one"));
+        png.append_chunk(chunk_from_strings("tEXt", "two"));
+
+        let messages: Vec<String> = png
+            .chunks_by_type("tEXt")
+            .iter()
+            .map(|chunk| chunk.data_as_string().unwrap())
+            .collect();
+        assert_eq!(messages, ["one", "two"]);
+        assert!(png.chunks_by_type("zTXt").is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let png = Png::try_from(&TINY_PNG[..]).unwrap();
+        assert!(png.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_missing_ihdr() {
+        let mut png = Png::try_from(&TINY_PNG[..]).unwrap();
+        png.remove_first_chunk("IHDR").unwrap();
+        assert!(matches!(png.validate(), Err(PngError::MissingIhdr)));
+        assert!(matches!(
+            Png::from_chunks(Vec::new()).validate(),
+            Err(PngError::MissingIhdr)
+        ));
+    }
+
+    #[test]
+    fn test_validate_missing_iend() {
+        let mut png = Png::try_from(&TINY_PNG[..]).unwrap();
+        png.remove_first_chunk("IEND").unwrap();
+        assert!(matches!(png.validate(), Err(PngError::MissingIend)));
+
+        png.append_chunk(Chunk::new(ChunkType::IEND, Vec::new()));
+        png.append_chunk(chunk_from_strings("ruSt", "after the end"));
+        assert!(matches!(png.validate(), Err(PngError::MissingIend)));
+    }
+
+    #[test]
+    fn test_as_bytes() {
+        let png = testing_png();
+        let expected: Vec<u8> = Png::STANDARD_HEADER
+            .iter()
+            .copied()
+            .chain(testing_chunks().iter().flat_map(Chunk::as_bytes))
+            .collect();
+        assert_eq!(png.as_bytes(), expected);
+    }
+
+    #[test]
+    fn test_empty_png_as_bytes() {
+        let png = Png::from_chunks(Vec::new());
+        assert_eq!(png.as_bytes(), Png::STANDARD_HEADER);
+    }
+
+    #[test]
+    fn test_to_writer() {
+        let png = testing_png();
+        let mut bytes = Vec::new();
+        png.to_writer(&mut bytes).unwrap();
+        assert_eq!(bytes, png.as_bytes());
+    }
+
+    #[test]
+    fn test_valid_from_bytes() {
+        let png = Png::try_from(&TINY_PNG[..]).unwrap();
+        let types: Vec<ChunkType> = png.chunks().iter().map(|c| *c.chunk_type()).collect();
+        assert_eq!(types, [ChunkType::IHDR, ChunkType::IDAT, ChunkType::IEND]);
+        assert_eq!(png.as_bytes(), TINY_PNG);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = testing_png().as_bytes();
+        let png = Png::try_from(bytes.as_ref()).unwrap();
+        assert_eq!(png.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let mut bytes = TINY_PNG;
+        bytes[1] = b'Q';
+        assert!(matches!(
+            Png::try_from(&bytes[..]),
+            Err(PngError::InvalidHeader)
+        ));
+        assert!(matches!(
+            Png::try_from(&TINY_PNG[..4]),
+            Err(PngError::InvalidHeader)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_chunk() {
+        let mut bytes = TINY_PNG;
+        // Corrupt the IHDR width without fixing its CRC.
+        bytes[19] = 2;
+        assert!(matches!(
+            Png::try_from(&bytes[..]),
+            Err(PngError::CrcMismatch { offset: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_crc_mismatch_reports_offset() {
+        let mut bytes = TINY_PNG;
+        // Corrupt the stored CRC of IDAT, the second chunk.
+        bytes[52] ^= 0xff;
+        let computed = u32::from_be_bytes([TINY_PNG[51], TINY_PNG[52], TINY_PNG[53], TINY_PNG[54]]);
+        for result in [Png::try_from(&bytes[..]), Png::from_reader(&bytes[..])] {
+            match result {
+                Err(PngError::CrcMismatch {
+                    offset,
+                    expected,
+                    actual,
+                }) => {
+                    assert_eq!(offset, 33);
+                    assert_eq!(expected, computed);
+                    assert_eq!(actual, computed ^ 0x00ff_0000);
+                }
+                other => panic!("expected a CRC mismatch, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated_chunk() {
+        assert!(matches!(
+            Png::try_from(&TINY_PNG[..TINY_PNG.len() - 1]),
+            Err(PngError::TruncatedChunk { offset }) if offset == TINY_PNG.len() - 12
+        ));
+    }
+
+    #[test]
+    fn test_from_bytes_lenient_stops_at_truncation() {
+        let (png, errors) = Png::from_bytes_lenient(&TINY_PNG);
+        assert_eq!(png.as_bytes(), TINY_PNG);
+        assert!(errors.is_empty());
+
+        // Cut the file off part way through IDAT.
+        let (png, errors) = Png::from_bytes_lenient(&TINY_PNG[..40]);
+        assert_eq!(png.chunks(), [tiny_header().to_chunk()]);
+        assert!(matches!(
+            errors[..],
+            [PngError::TruncatedChunk { offset: 33 }]
+        ));
+    }
+
+    #[test]
+    fn test_from_bytes_lenient_skips_bad_crc() {
+        let mut bytes = TINY_PNG.to_vec();
+        bytes[0] = 0;
+        bytes[52] ^= 0xff;
+        bytes.extend_from_slice(b"junk");
+        let (png, errors) = Png::from_bytes_lenient(&bytes);
+
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["IHDR", "IEND"]);
+        assert!(matches!(
+            errors[..],
+            [
+                PngError::InvalidHeader,
+                PngError::CrcMismatch { offset: 33, .. },
+                PngError::TrailingData(4)
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_trailing_data() {
+        let mut bytes = TINY_PNG.to_vec();
+        bytes.extend_from_slice(b"extra");
+        assert!(matches!(
+            Png::try_from(bytes.as_ref()),
+            Err(PngError::TrailingData(5))
+        ));
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let png = Png::from_reader(io::Cursor::new(TINY_PNG)).unwrap();
+        let types: Vec<ChunkType> = png.chunks().iter().map(|c| *c.chunk_type()).collect();
+        assert_eq!(types, [ChunkType::IHDR, ChunkType::IDAT, ChunkType::IEND]);
+        assert_eq!(png.as_bytes(), TINY_PNG);
+    }
+
+    #[test]
+    fn test_from_reader_matches_try_from() {
+        let bytes = testing_png().as_bytes();
+        let png = Png::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(png.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_from_reader_invalid_header() {
+        assert!(matches!(
+            Png::from_reader(&TINY_PNG[..4]),
+            Err(PngError::InvalidHeader)
+        ));
+        let mut bytes = TINY_PNG;
+        bytes[0] = 0;
+        assert!(matches!(
+            Png::from_reader(&bytes[..]),
+            Err(PngError::InvalidHeader)
+        ));
+    }
+
+    #[test]
+    fn test_from_reader_truncated() {
+        for (len, expected) in [(10, 8), (30, 8), (40, 33), (TINY_PNG.len() - 1, 55)] {
+            assert!(matches!(
+                Png::from_reader(&TINY_PNG[..len]),
+                Err(PngError::TruncatedChunk { offset }) if offset == expected
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_reader_trailing_data() {
+        let mut bytes = TINY_PNG.to_vec();
+        bytes.extend_from_slice(b"extra");
+        assert!(matches!(
+            Png::from_reader(bytes.as_slice()),
+            Err(PngError::TrailingData(5))
+        ));
+    }
+
+    #[test]
+    fn test_keep_trailing_data_round_trips() {
+        let mut bytes = TINY_PNG.to_vec();
+        bytes.extend_from_slice(b"hidden after IEND");
+        let options = PngReadOptions {
+            keep_trailing_data: true,
+            ..PngReadOptions::default()
+        };
+        let png = Png::from_reader_with_options(bytes.as_slice(), &options).unwrap();
+
+        assert_eq!(png.trailing_bytes(), b"hidden after IEND");
+        assert_eq!(png.chunks().len(), 3);
+        assert_eq!(png.as_bytes(), bytes);
+        assert_eq!(png.byte_size(), bytes.len());
+        let mut written = Vec::new();
+        png.to_writer(&mut written).unwrap();
+        assert_eq!(written, bytes);
+
+        let png = Png::from_reader_with_options(&TINY_PNG[..], &options).unwrap();
+        assert!(png.trailing_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_len_and_is_empty() {
+        let png = testing_png();
+        assert_eq!(png.len(), png.chunks().len());
+        assert!(!png.is_empty());
+        assert_eq!(Png::default().len(), 0);
+        assert!(Png::default().is_empty());
+    }
+
+    #[test]
+    fn test_recompute_crcs() {
+        let mut bytes = TINY_PNG;
+        bytes[52] ^= 0xff;
+        let options = PngReadOptions {
+            verify_crcs: false,
+            ..PngReadOptions::default()
+        };
+        let mut png = Png::from_reader_with_options(&bytes[..], &options).unwrap();
+        assert_eq!(png.as_bytes(), bytes);
+        assert!(!png.chunks()[1].crc_is_valid());
+
+        assert_eq!(png.recompute_crcs(), 1);
+        assert_eq!(png.as_bytes(), TINY_PNG);
+        assert_eq!(png.recompute_crcs(), 0);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tiny.png");
+        fs::write(&path, TINY_PNG).unwrap();
+
+        let mut png = Png::from_file(&path).unwrap();
+        png.append_chunk(chunk_from_strings("ruSt", "hidden"));
+        png.write_file(&path).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(bytes, png.as_bytes());
+    }
+
+    #[test]
+    fn test_write_file_leaves_no_temporary_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tiny.png");
+        testing_png().write_file(&path).unwrap();
+
+        let entries: Vec<_> = fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[test]
+    fn test_failed_write_keeps_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tiny.png");
+        fs::write(&path, TINY_PNG).unwrap();
+        // A directory in the way of the temporary file makes the write fail.
+        fs::create_dir(dir.path().join("tiny.png.pngme-tmp")).unwrap();
+
+        assert!(testing_png().write_file(&path).is_err());
+        assert_eq!(fs::read(&path).unwrap(), TINY_PNG);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tiny.png");
+        fs::write(&path, TINY_PNG).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        testing_png().write_file(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_follows_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("tiny.png");
+        let link = dir.path().join("link.png");
+        fs::write(&target, TINY_PNG).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let png = testing_png();
+        png.write_file(&link).unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read(&target).unwrap(), png.as_bytes());
+    }
+
+    #[test]
+    fn test_from_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            Png::from_file(dir.path().join("missing.png")),
+            Err(PngError::Io(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_png_serde_round_trip() {
+        let png = Png::try_from(&TINY_PNG[..]).unwrap();
+        let json = serde_json::to_string(&png).unwrap();
+        let parsed: Png = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.as_bytes(), TINY_PNG);
+    }
+
+    #[test]
+    fn test_length_too_large() {
+        let mut bytes = Png::STANDARD_HEADER.to_vec();
+        bytes.extend_from_slice(&[0x80, 0, 0, 0]);
+        bytes.extend_from_slice(b"IDAT");
+        assert!(matches!(
+            Png::try_from(bytes.as_ref()),
+            Err(PngError::Chunk {
+                offset: 8,
+                error: ChunkError::LengthTooLarge(0x8000_0000)
+            })
+        ));
+        assert!(matches!(
+            Png::from_reader(bytes.as_slice()),
+            Err(PngError::Chunk {
+                offset: 8,
+                error: ChunkError::LengthTooLarge(0x8000_0000)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_visit_chunks() {
+        let mut types = Vec::new();
+        Png::visit_chunks(&TINY_PNG[..], |chunk| {
+            types.push(chunk.chunk_type().to_string());
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(types, ["IHDR", "IDAT", "IEND"]);
+    }
+
+    #[test]
+    fn test_visit_chunks_stops_early() {
+        /// Fails the test if anything past the bytes it was given is read.
+        struct Unreachable;
+
+        impl Read for Unreachable {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                panic!("read past the chunk that stopped the visit");
+            }
+        }
+
+        let mut visited = Vec::new();
+        Png::visit_chunks((&TINY_PNG[..33]).chain(Unreachable), |chunk| {
+            visited.push(*chunk.chunk_type());
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert_eq!(visited, [ChunkType::IHDR]);
+    }
+
+    #[test]
+    fn test_visit_chunks_reports_errors() {
+        let mut bytes = TINY_PNG;
+        bytes[52] ^= 0xff;
+        let mut count = 0;
+        let err = Png::visit_chunks(&bytes[..], |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        })
+        .unwrap_err();
+        assert!(matches!(err, PngError::CrcMismatch { offset: 33, .. }));
+        assert_eq!(count, 1);
+        assert!(matches!(
+            Png::visit_chunks(&b"not a png"[..], |_| ControlFlow::Continue(())),
+            Err(PngError::InvalidHeader)
+        ));
+    }
+
+    #[test]
+    fn test_from_reader_with_options() {
+        let options = PngReadOptions {
+            max_chunk_size: 12,
+            ..PngReadOptions::default()
+        };
+        assert!(matches!(
+            Png::from_reader_with_options(&TINY_PNG[..], &options),
+            Err(PngError::Chunk {
+                offset: 8,
+                error: ChunkError::ChunkTooLarge {
+                    declared: 13,
+                    limit: 12
+                }
+            })
+        ));
+
+        let options = PngReadOptions {
+            max_chunk_size: 13,
+            ..PngReadOptions::default()
+        };
+        let png = Png::from_reader_with_options(&TINY_PNG[..], &options).unwrap();
+        assert_eq!(png.as_bytes(), TINY_PNG);
+    }
+
+    #[test]
+    fn test_byte_size() {
+        let png = testing_png();
+        assert_eq!(png.byte_size(), png.as_bytes().len());
+        assert_eq!(Png::try_from(&TINY_PNG[..]).unwrap().byte_size(), 67);
+        assert_eq!(Png::from_chunks(Vec::new()).byte_size(), 8);
+    }
+
+    #[test]
+    fn test_ancillary_and_critical_chunks() {
+        let png = testing_png();
+        let types = |chunks: Vec<&Chunk>| -> Vec<String> {
+            chunks
+                .iter()
+                .map(|chunk| chunk.chunk_type().to_string())
+                .collect()
+        };
+        assert_eq!(types(png.ancillary_chunks()), ["miDl"]);
+        assert_eq!(types(png.critical_chunks()), ["FrSt", "LASt"]);
+    }
+
+    #[test]
+    fn test_strip_ancillary_chunks() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("tEXt", "comment"));
+        assert_eq!(png.strip_ancillary_chunks(), 2);
+
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["FrSt", "LASt"]);
+        assert_eq!(png.strip_ancillary_chunks(), 0);
+    }
+
+    #[test]
+    fn test_dedup_chunks() {
+        let mut png = testing_png();
+        for _ in 0..3 {
+            png.insert_chunk(2, chunk_from_strings("ruSt", "secret"))
+                .unwrap();
+        }
+        png.append_chunk(chunk_from_strings("ruSt", "other"));
+        assert_eq!(png.dedup_chunks(), 2);
+
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["FrSt", "miDl", "ruSt", "LASt", "ruSt"]);
+        assert_eq!(png.dedup_chunks(), 0);
+    }
+
+    #[test]
+    fn test_dedup_chunks_ignores_stale_crc() {
+        let mut bytes = TINY_PNG[..55].to_vec();
+        bytes.extend_from_slice(&TINY_PNG[33..55]);
+        *bytes.last_mut().unwrap() ^= 0xff;
+        bytes.extend_from_slice(&TINY_PNG[55..]);
+        let options = PngReadOptions {
+            verify_crcs: false,
+            ..PngReadOptions::default()
+        };
+        let mut png = Png::from_reader_with_options(&bytes[..], &options).unwrap();
+        assert_eq!(png.dedup_chunks(), 1);
+        assert_eq!(png.as_bytes(), TINY_PNG);
+    }
+
+    #[test]
+    fn test_rename_chunk_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("ruSt", "one"));
+        png.append_chunk(chunk_from_strings("ruSt", "two"));
+        assert_eq!(png.rename_chunk_type("ruSt", "teXt").unwrap(), 2);
+
+        assert!(png.chunk_by_type("ruSt").is_none());
+        let renamed = png.chunks_by_type("teXt");
+        assert_eq!(renamed.len(), 2);
+        assert_eq!(*renamed[0], chunk_from_strings("teXt", "one"));
+        assert_eq!(renamed[1].crc(), crc32(b"teXttwo"));
+        assert_eq!(png.rename_chunk_type("ruSt", "teXt").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_rename_chunk_type_rejects_invalid_type() {
+        let mut png = testing_png();
+        assert!(matches!(
+            png.rename_chunk_type("miDl", "mi1l"),
+            Err(Error::ChunkType(ChunkTypeError::NonAsciiByte {
+                index: 2,
+                ..
+            }))
+        ));
+        assert!(png.chunk_by_type("miDl").is_some());
+    }
+
+    #[test]
+    fn test_is_png() {
+        assert!(is_png(&TINY_PNG));
+        assert!(is_png(&Png::STANDARD_HEADER));
+        assert!(!is_png(&[0xff, 0xd8, 0xff, 0xe0, 0, 16, b'J', b'F']));
+        assert!(!is_png(&Png::STANDARD_HEADER[..7]));
+    }
+
+    #[test]
+    fn test_has_png_signature() {
+        assert!(has_png_signature(&TINY_PNG[..]).unwrap());
+        assert!(!has_png_signature(&[0xff, 0xd8, 0xff, 0xe0, 0, 16, b'J', b'F'][..]).unwrap());
+        assert!(!has_png_signature(&[0xff, 0xd8][..]).unwrap());
+    }
+
+    #[test]
+    fn test_verify() {
+        let png = Png::try_from(&TINY_PNG[..]).unwrap();
+        assert!(png.verify().is_ok());
+
+        let errors = testing_png().verify().unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [PngError::MissingIhdr, PngError::MissingIend]
+        ));
+    }
+
+    #[test]
+    fn test_verify_bytes_collects_every_problem() {
+        assert!(verify_bytes(&TINY_PNG).is_ok());
+
+        let mut bytes = TINY_PNG.to_vec();
+        // Corrupt the CRCs of IHDR and IDAT, and drop IEND.
+        bytes[30] ^= 0xff;
+        bytes[52] ^= 0xff;
+        bytes.truncate(bytes.len() - 12);
+        let errors = verify_bytes(&bytes).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [
+                PngError::CrcMismatch { offset: 8, .. },
+                PngError::CrcMismatch { offset: 33, .. },
+                PngError::MissingIend,
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_verify_bytes_header_and_trailing_data() {
+        let mut bytes = TINY_PNG.to_vec();
+        bytes[0] = 0;
+        bytes.extend_from_slice(b"junk");
+        let errors = verify_bytes(&bytes).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [PngError::InvalidHeader, PngError::TrailingData(4)]
+        ));
+
+        let errors = verify_bytes(&TINY_PNG[..40]).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [
+                PngError::TruncatedChunk { offset: 33 },
+                PngError::MissingIend
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert_eq!(
+            Png::try_from(&TINY_PNG[..]).unwrap().dimensions().unwrap(),
+            (1, 1)
+        );
+
+        let ihdr = Chunk::new(
+            ChunkType::IHDR,
+            vec![0, 0, 2, 128, 0, 0, 1, 224, 8, 6, 0, 0, 0],
+        );
+        let png = Png::from_chunks(vec![ihdr, Chunk::new(ChunkType::IEND, Vec::new())]);
+        assert_eq!(png.dimensions().unwrap(), (640, 480));
+    }
+
+    #[test]
+    fn test_dimensions_missing_or_malformed_ihdr() {
+        assert!(matches!(
+            testing_png().dimensions(),
+            Err(Error::Png(PngError::MissingIhdr))
+        ));
+
+        let png = Png::from_chunks(vec![Chunk::new(ChunkType::IHDR, vec![0; 4])]);
+        assert!(matches!(
+            png.dimensions(),
+            Err(Error::Png(PngError::Ihdr(IhdrError::InvalidLength(4))))
+        ));
+    }
+
+    #[test]
+    fn test_replace_chunk_data() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "second"));
+        png.replace_chunk_data("miDl", b"edited".to_vec()).unwrap();
+
+        assert_eq!(png.chunks()[1], chunk_from_strings("miDl", "edited"));
+        assert_eq!(png.chunks()[3].data(), b"second");
+        let reparsed = Png::try_from(png.as_bytes().as_ref()).unwrap();
+        assert_eq!(reparsed.chunks()[1].data(), b"edited");
+    }
+
+    #[test]
+    fn test_replace_missing_chunk_data() {
+        let mut png = testing_png();
+        assert!(matches!(
+            png.replace_chunk_data("ruSt", Vec::new()),
+            Err(Error::Png(PngError::ChunkNotFound(_)))
+        ));
+    }
+
+    fn tiny_header() -> ImageHeader {
+        ImageHeader::from_chunk(&Chunk::try_from(&TINY_PNG[8..]).unwrap()).unwrap()
+    }
+
+    fn tiny_idat() -> Chunk {
+        Chunk::try_from(&TINY_PNG[33..]).unwrap()
+    }
+
+    #[test]
+    fn test_builder() {
+        let png = PngBuilder::new()
+            .header(tiny_header())
+            .add_chunk(tiny_idat())
+            .build()
+            .unwrap();
+        assert_eq!(png.as_bytes(), TINY_PNG);
+        assert!(png.verify().is_ok());
+        assert!(Png::try_from(png.as_bytes().as_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_builder_keeps_explicit_ihdr_and_iend() {
+        let png = PngBuilder::new()
+            .add_chunk(tiny_header().to_chunk())
+            .add_chunk(tiny_idat())
+            .add_chunk(Chunk::new(ChunkType::IEND, Vec::new()))
+            .build()
+            .unwrap();
+        assert_eq!(png.as_bytes(), TINY_PNG);
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        assert!(matches!(
+            PngBuilder::new().add_chunk(tiny_idat()).build(),
+            Err(Error::Png(PngError::MissingIhdr))
+        ));
+        assert!(matches!(
+            PngBuilder::new().header(tiny_header()).build(),
+            Err(Error::Png(PngError::MissingIdat))
+        ));
+        assert!(matches!(
+            PngBuilder::new()
+                .header(tiny_header())
+                .add_chunk(tiny_header().to_chunk())
+                .add_chunk(tiny_idat())
+                .build(),
+            Err(Error::Png(PngError::DuplicateIhdr))
+        ));
+        assert!(matches!(
+            PngBuilder::new()
+                .header(tiny_header())
+                .add_chunk(Chunk::new(ChunkType::IEND, Vec::new()))
+                .add_chunk(tiny_idat())
+                .build(),
+            Err(Error::Png(PngError::MisplacedIend))
+        ));
+
+        let png = PngBuilder::new()
+            .header(tiny_header())
+            .allow_missing_idat(true)
+            .build()
+            .unwrap();
+        let types: Vec<String> = png
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["IHDR", "IEND"]);
+    }
+
+    #[test]
+    fn test_search() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("ruSt", "hidden aaa"));
+
+        assert_eq!(
+            png.search(b"chunk"),
+            [
+                (0, ChunkType::from_str("FrSt").unwrap(), 15),
+                (1, ChunkType::from_str("miDl").unwrap(), 13),
+                (2, ChunkType::from_str("LASt").unwrap(), 14),
+            ]
+        );
+        assert_eq!(
+            png.search(b"aa"),
+            [
+                (3, ChunkType::from_str("ruSt").unwrap(), 7),
+                (3, ChunkType::from_str("ruSt").unwrap(), 8),
+            ]
+        );
+        assert!(png.search(b"absent").is_empty());
+        assert!(png.search(b"").is_empty());
+    }
+
+    #[test]
+    fn test_remove_first_chunks() {
+        let mut png = testing_png();
+        let results = png.remove_first_chunks(&["miDl", "ruSt", "FrSt"]);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().data(), b"I am another chunk");
+        assert!(matches!(
+            results[1],
+            Err(Error::Png(PngError::ChunkNotFound(_)))
+        ));
+        assert_eq!(results[2].as_ref().unwrap().data(), b"I am the first chunk");
+        assert_eq!(png.chunks().len(), 1);
+    }
+
+    #[test]
+    fn test_extract_messages() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("ruSt", "first"));
+        png.append_chunk(Chunk::new(
+            ChunkType::from_str("ruSt").unwrap(),
+            vec![0xff, 0xfe],
+        ));
+        png.append_chunk(chunk_from_strings("ruSt", "third"));
+
+        let messages = png.extract_messages("ruSt");
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].as_ref().unwrap(), "first");
+        assert!(matches!(
+            messages[1],
+            Err(Error::Chunk(ChunkError::InvalidUtf8))
+        ));
+        assert_eq!(messages[2].as_ref().unwrap(), "third");
+        assert!(png.extract_messages("nOne").is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let original = Png::try_from(&TINY_PNG[..]).unwrap();
+        assert!(original.diff(&original).is_empty());
+
+        let mut edited = original.clone();
+        edited
+            .insert_chunk(2, chunk_from_strings("ruSt", "secret"))
+            .unwrap();
+        let hidden = ChunkType::from_str("ruSt").unwrap();
+        assert_eq!(
+            original.diff(&edited),
+            PngDiff {
+                added: vec![(hidden, 0)],
+                ..PngDiff::default()
+            }
+        );
+        assert_eq!(edited.diff(&original).removed, [(hidden, 0)]);
+
+        edited.replace_chunk_data("IDAT", Vec::new()).unwrap();
+        assert_eq!(original.diff(&edited).modified, [(ChunkType::IDAT, 0)]);
+    }
+
+    #[test]
+    fn test_chunk_by_type_ignore_case() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("ruSt", "one"));
+        png.append_chunk(chunk_from_strings("RUST", "two"));
+
+        assert!(png.chunk_by_type("rust").is_none());
+        let chunk = png.chunk_by_type_ignore_case("rust").unwrap();
+        assert_eq!(chunk.data(), b"one");
+        let datas: Vec<&[u8]> = png
+            .chunks_by_type_ignore_case("rust")
+            .into_iter()
+            .map(Chunk::data)
+            .collect();
+        assert_eq!(datas, [&b"one"[..], b"two"]);
+        assert!(png.chunk_by_type_ignore_case("rus").is_none());
+    }
+
+    #[test]
+    fn test_missing_critical_chunks() {
+        let tiny = Png::try_from(&TINY_PNG[..]).unwrap();
+        assert!(tiny.missing_critical_chunks().is_empty());
+
+        let mut png = tiny.clone();
+        png.remove_first_chunk("IDAT").unwrap();
+        assert_eq!(png.missing_critical_chunks(), [ChunkType::IDAT]);
+
+        assert_eq!(
+            testing_png().missing_critical_chunks(),
+            [ChunkType::IHDR, ChunkType::IDAT, ChunkType::IEND]
+        );
+    }
+
+    #[test]
+    fn test_validate_palette() {
+        let tiny = Png::try_from(&TINY_PNG[..]).unwrap();
+        assert!(tiny.validate_palette().is_ok());
+        assert!(matches!(
+            testing_png().validate_palette(),
+            Err(Error::Png(PngError::MissingIhdr))
+        ));
+
+        let plte = Chunk::new(ChunkType::PLTE, vec![0, 0, 0]);
+        let header = ImageHeader {
+            color_type: ColorType::Rgb,
+            ..tiny_header()
+        };
+        let mut png = PngBuilder::new()
+            .header(header)
+            .add_chunk(tiny_idat())
+            .build()
+            .unwrap();
+        assert!(png.validate_palette().is_ok());
+        png.insert_chunk(1, plte).unwrap();
+        assert!(png.validate_palette().is_ok());
+    }
+
+    #[test]
+    fn test_validate_palette_required() {
+        let header = ImageHeader {
+            color_type: ColorType::Palette,
+            ..tiny_header()
+        };
+        let mut png = PngBuilder::new()
+            .header(header)
+            .add_chunk(tiny_idat())
+            .build()
+            .unwrap();
+        assert!(matches!(
+            png.validate_palette(),
+            Err(Error::Png(PngError::PaletteRequired))
+        ));
+
+        png.insert_chunk(1, Chunk::new(ChunkType::PLTE, vec![0, 0, 0]))
+            .unwrap();
+        assert!(png.validate_palette().is_ok());
+    }
+
+    #[test]
+    fn test_validate_palette_forbidden() {
+        let mut png = Png::try_from(&TINY_PNG[..]).unwrap();
+        png.insert_chunk(1, Chunk::new(ChunkType::PLTE, vec![0, 0, 0]))
+            .unwrap();
+        assert!(matches!(
+            png.validate_palette(),
+            Err(Error::Png(PngError::PaletteForbidden(ColorType::Grayscale)))
+        ));
+    }
+
+    #[test]
+    fn test_missing_palette_for_indexed_color() {
+        let header = ImageHeader {
+            color_type: ColorType::Palette,
+            ..tiny_header()
+        };
+        let mut png = PngBuilder::new()
+            .header(header)
+            .add_chunk(tiny_idat())
+            .build()
+            .unwrap();
+        assert_eq!(png.missing_critical_chunks(), [ChunkType::PLTE]);
+
+        png.insert_chunk(1, Chunk::new(ChunkType::PLTE, vec![0, 0, 0]))
+            .unwrap();
+        assert!(png.missing_critical_chunks().is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+//! `tEXt` chunks: a Latin-1 keyword, a NUL, then Latin-1 text.
+
+use std::fmt;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+
+/// Longest keyword the PNG spec allows, in bytes.
+pub(crate) const MAX_KEYWORD_LEN: usize = 79;
+
+/// The keyword and value of a `tEXt` chunk, as returned by [`parse_text`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEntry {
+    pub keyword: String,
+    pub value: String,
+}
+
+impl Chunk {
+    /// Creates a `tEXt` chunk storing `value` under `keyword`, e.g. `Comment` or `Author`.
+    ///
+    /// The keyword must be 1 to 79 printable Latin-1 characters with no leading, trailing
+    /// or consecutive spaces, and the value must be Latin-1 without NULs.
+    pub fn new_text_chunk(keyword: &str, value: &str) -> crate::Result<Chunk> {
+        let mut data =
+            encode_keyword(keyword).ok_or_else(|| TextError::InvalidKeyword(keyword.into()))?;
+        data.push(0);
+        data.extend(encode_latin1(value).map_err(TextError::InvalidValue)?);
+        Ok(Chunk::new(ChunkType::TEXT, data))
+    }
+}
+
+/// Parses the keyword and value of a `tEXt` chunk.
+pub fn parse_text(chunk: &Chunk) -> crate::Result<TextEntry> {
+    if *chunk.chunk_type() != ChunkType::TEXT {
+        return Err(TextError::WrongChunkType(*chunk.chunk_type()).into());
+    }
+    let data = chunk.data();
+    let nul = data
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(TextError::MissingSeparator)?;
+    Ok(TextEntry {
+        keyword: decode_latin1(&data[..nul]),
+        value: decode_latin1(&data[nul + 1..]),
+    })
+}
+
+/// Checks `keyword` against the spec's rules for `tEXt`, `zTXt` and `iTXt` keywords and
+/// encodes it as Latin-1, or returns `None` if it breaks them.
+pub(crate) fn encode_keyword(keyword: &str) -> Option<Vec<u8>> {
+    let bytes = keyword
+        .chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{a1}'..='\u{ff}' => Some(c as u8),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    let valid = !bytes.is_empty()
+        && bytes.len() <= MAX_KEYWORD_LEN
+        && !bytes.starts_with(b" ")
+        && !bytes.ends_with(b" ")
+        && !bytes.windows(2).any(|pair| pair == b"  ");
+    valid.then_some(bytes)
+}
+
+/// Encodes `text` as Latin-1, or returns the first character that is a NUL or outside
+/// Latin-1, neither of which `tEXt` or `zTXt` text may contain.
+pub(crate) fn encode_latin1(text: &str) -> Result<Vec<u8>, char> {
+    text.chars()
+        .map(|c| match c {
+            '\u{1}'..='\u{ff}' => Ok(c as u8),
+            _ => Err(c),
+        })
+        .collect()
+}
+
+/// Every byte is a Latin-1 character with the same code point.
+pub(crate) fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| char::from(b)).collect()
+}
+
+/// Errors produced when building or parsing a `tEXt` chunk.
+#[derive(Debug)]
+pub enum TextError {
+    /// The keyword breaks the spec's length or character rules; carries the keyword.
+    InvalidKeyword(String),
+    /// The value contains a NUL or a character outside Latin-1; carries the character.
+    InvalidValue(char),
+    /// The chunk is not a `tEXt` chunk; carries its actual type.
+    WrongChunkType(ChunkType),
+    /// The data has no NUL separating the keyword from the value.
+    MissingSeparator,
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::InvalidKeyword(keyword) => write!(
+                f,
+                "Invalid tEXt keyword {:?}: must be 1 to {} printable Latin-1 characters \
+                 without leading, trailing or repeated spaces",
+                keyword, MAX_KEYWORD_LEN
+            ),
+            TextError::InvalidValue(c) => {
+                write!(f, "tEXt value contains {:?}, which is not allowed", c)
+            }
+            TextError::WrongChunkType(chunk_type) => {
+                write!(f, "Expected a tEXt chunk, found {}", chunk_type)
+            }
+            TextError::MissingSeparator => {
+                write!(f, "tEXt chunk has no NUL separating keyword and value")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_new_text_chunk_layout() {
+        let chunk = Chunk::new_text_chunk("Author", "Zo\u{eb}").unwrap();
+        assert_eq!(*chunk.chunk_type(), ChunkType::TEXT);
+        assert_eq!(chunk.data(), b"Author\0Zo\xeb");
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let chunk = Chunk::new_text_chunk("Comment", "line one\nline two").unwrap();
+        assert_eq!(
+            parse_text(&chunk).unwrap(),
+            TextEntry {
+                keyword: "Comment".to_string(),
+                value: "line one\nline two".to_string(),
+            }
+        );
+
+        let chunk = Chunk::new_text_chunk("Title", "").unwrap();
+        assert_eq!(parse_text(&chunk).unwrap().value, "");
+    }
+
+    #[test]
+    fn test_new_text_chunk_rejects_invalid_input() {
+        for keyword in ["", " lead", "trail ", "two  spaces", "\u{263a}"] {
+            assert!(matches!(
+                Chunk::new_text_chunk(keyword, "value"),
+                Err(Error::Text(TextError::InvalidKeyword(_)))
+            ));
+        }
+        assert!(matches!(
+            Chunk::new_text_chunk("Comment", "smile \u{263a}"),
+            Err(Error::Text(TextError::InvalidValue('\u{263a}')))
+        ));
+        assert!(matches!(
+            Chunk::new_text_chunk("Comment", "a\0b"),
+            Err(Error::Text(TextError::InvalidValue('\0')))
+        ));
+    }
+
+    #[test]
+    fn test_parse_text_errors() {
+        let chunk = Chunk::new(ChunkType::TEXT, b"no separator".to_vec());
+        assert!(matches!(
+            parse_text(&chunk),
+            Err(Error::Text(TextError::MissingSeparator))
+        ));
+
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"a\0b".to_vec());
+        assert!(matches!(
+            parse_text(&chunk),
+            Err(Error::Text(TextError::WrongChunkType(_)))
+        ));
+    }
+}
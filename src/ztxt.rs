@@ -0,0 +1,187 @@
+//! `zTXt` chunks: a Latin-1 keyword, a NUL, the compression method byte, then
+//! zlib-compressed Latin-1 text.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::payload::inflate;
+use crate::text::{decode_latin1, encode_keyword, encode_latin1, TextEntry, MAX_KEYWORD_LEN};
+
+/// The only compression method the spec defines: zlib deflate.
+const COMPRESSION_ZLIB: u8 = 0;
+
+impl Chunk {
+    /// Creates a `zTXt` chunk storing `text` zlib-compressed under `keyword`.
+    ///
+    /// The keyword must follow the same rules as for [`Chunk::new_text_chunk`], and the
+    /// text must be Latin-1 without NULs.
+    pub fn new_ztxt(keyword: &str, text: &str) -> crate::Result<Chunk> {
+        let mut data =
+            encode_keyword(keyword).ok_or_else(|| ZTxtError::InvalidKeyword(keyword.into()))?;
+        let text = encode_latin1(text).map_err(ZTxtError::InvalidText)?;
+        data.extend_from_slice(&[0, COMPRESSION_ZLIB]);
+        let mut encoder = ZlibEncoder::new(data, Compression::best());
+        encoder
+            .write_all(&text)
+            .expect("writing to a Vec never fails");
+        let data = encoder.finish().expect("writing to a Vec never fails");
+        Ok(Chunk::new(ChunkType::ZTXT, data))
+    }
+}
+
+/// Parses the keyword of a `zTXt` chunk and inflates its text.
+pub fn parse_ztxt(chunk: &Chunk) -> crate::Result<TextEntry> {
+    Ok(parse(chunk)?)
+}
+
+fn parse(chunk: &Chunk) -> Result<TextEntry, ZTxtError> {
+    if *chunk.chunk_type() != ChunkType::ZTXT {
+        return Err(ZTxtError::WrongChunkType(*chunk.chunk_type()));
+    }
+    let data = chunk.data();
+    let nul = data
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(ZTxtError::Truncated)?;
+    let (method, compressed) = match &data[nul + 1..] {
+        [method, rest @ ..] => (*method, rest),
+        [] => return Err(ZTxtError::Truncated),
+    };
+    if method != COMPRESSION_ZLIB {
+        return Err(ZTxtError::UnsupportedCompression(method));
+    }
+
+    let text = inflate(ZlibDecoder::new(compressed)).map_err(ZTxtError::Decompress)?;
+    Ok(TextEntry {
+        keyword: decode_latin1(&data[..nul]),
+        value: decode_latin1(&text),
+    })
+}
+
+/// Errors produced when building or parsing a `zTXt` chunk.
+#[derive(Debug)]
+pub enum ZTxtError {
+    /// The keyword breaks the spec's length or character rules; carries the keyword.
+    InvalidKeyword(String),
+    /// The text contains a NUL or a character outside Latin-1; carries the character.
+    InvalidText(char),
+    /// The chunk is not a `zTXt` chunk; carries its actual type.
+    WrongChunkType(ChunkType),
+    /// The data has no NUL after the keyword, or no compression method byte.
+    Truncated,
+    /// The text is compressed with a method other than zlib; carries the method.
+    UnsupportedCompression(u8),
+    /// The compressed text could not be inflated.
+    Decompress(io::Error),
+}
+
+impl fmt::Display for ZTxtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZTxtError::InvalidKeyword(keyword) => write!(
+                f,
+                "Invalid zTXt keyword {:?}: must be 1 to {} printable Latin-1 characters \
+                 without leading, trailing or repeated spaces",
+                keyword, MAX_KEYWORD_LEN
+            ),
+            ZTxtError::InvalidText(c) => {
+                write!(f, "zTXt text contains {:?}, which is not allowed", c)
+            }
+            ZTxtError::WrongChunkType(chunk_type) => {
+                write!(f, "Expected a zTXt chunk, found {}", chunk_type)
+            }
+            ZTxtError::Truncated => {
+                write!(
+                    f,
+                    "zTXt chunk is missing its separator or compression method"
+                )
+            }
+            ZTxtError::UnsupportedCompression(method) => {
+                write!(f, "Unsupported zTXt compression method {}", method)
+            }
+            ZTxtError::Decompress(e) => write!(f, "Failed to decompress zTXt text: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ZTxtError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ZTxtError::Decompress(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_ztxt_round_trip() {
+        let text = "Zo\u{eb} wrote this\n".repeat(20);
+        let chunk = Chunk::new_ztxt("Comment", &text).unwrap();
+        assert_eq!(*chunk.chunk_type(), ChunkType::ZTXT);
+        assert_eq!(&chunk.data()[..9], b"Comment\0\0");
+        assert!(chunk.data().len() < text.len());
+        assert_eq!(
+            parse_ztxt(&chunk).unwrap(),
+            TextEntry {
+                keyword: "Comment".to_string(),
+                value: text,
+            }
+        );
+
+        let chunk = Chunk::new_ztxt("Title", "").unwrap();
+        assert_eq!(parse_ztxt(&chunk).unwrap().value, "");
+    }
+
+    #[test]
+    fn test_new_ztxt_rejects_invalid_input() {
+        assert!(matches!(
+            Chunk::new_ztxt(" lead", "text"),
+            Err(Error::ZTxt(ZTxtError::InvalidKeyword(_)))
+        ));
+        assert!(matches!(
+            Chunk::new_ztxt("Comment", "smile \u{263a}"),
+            Err(Error::ZTxt(ZTxtError::InvalidText('\u{263a}')))
+        ));
+    }
+
+    #[test]
+    fn test_parse_ztxt_errors() {
+        let mut data = Chunk::new_ztxt("Comment", "text").unwrap().data().to_vec();
+        data[8] = 1;
+        assert!(matches!(
+            parse_ztxt(&Chunk::new(ChunkType::ZTXT, data)),
+            Err(Error::ZTxt(ZTxtError::UnsupportedCompression(1)))
+        ));
+
+        for data in [&b"no separator"[..], b"Comment\0"] {
+            let chunk = Chunk::new(ChunkType::ZTXT, data.to_vec());
+            assert!(matches!(
+                parse_ztxt(&chunk),
+                Err(Error::ZTxt(ZTxtError::Truncated))
+            ));
+        }
+
+        let chunk = Chunk::new(ChunkType::ZTXT, b"Comment\0\0not zlib".to_vec());
+        assert!(matches!(
+            parse_ztxt(&chunk),
+            Err(Error::ZTxt(ZTxtError::Decompress(_)))
+        ));
+
+        let chunk = Chunk::new(ChunkType::TEXT, b"Comment\0\0".to_vec());
+        assert!(matches!(
+            parse_ztxt(&chunk),
+            Err(Error::ZTxt(ZTxtError::WrongChunkType(ChunkType::TEXT)))
+        ));
+    }
+}
@@ -0,0 +1,14 @@
+use std::str::FromStr;
+
+use pngme::ChunkType;
+
+#[test]
+fn chunk_type_is_usable_outside_the_crate() {
+    let chunk_type = ChunkType::from_str("ruSt").unwrap();
+    assert_eq!(chunk_type.bytes(), *b"ruSt");
+    assert!(!chunk_type.is_critical());
+    assert!(!chunk_type.is_public());
+    assert!(chunk_type.is_reserved_bit_valid());
+    assert!(chunk_type.is_safe_to_copy());
+    assert!(chunk_type.is_valid());
+}
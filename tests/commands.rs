@@ -0,0 +1,940 @@
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use clap::Parser;
+use pngme::args::Cli;
+use pngme::{commands, Chunk, ChunkError, ChunkType, Error, Png, PngError};
+
+/// Parses `args` as a pngme command line and runs it, returning what it printed.
+fn pngme(args: &[&str]) -> pngme::Result<String> {
+    let cli = Cli::try_parse_from(std::iter::once("pngme").chain(args.iter().copied())).unwrap();
+    let mut out = Vec::new();
+    commands::run(&cli.command, cli.verbosity(), &mut out)?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn encode_adds_chunk_before_iend() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    pngme(&["encode", path_str(&path), "ruSt", "secret"]).unwrap();
+
+    let png = Png::from_file(&path).unwrap();
+    let chunk = png.chunk_by_type("ruSt").unwrap();
+    assert_eq!(chunk.data_as_string().unwrap(), "secret");
+    assert_eq!(*png.chunks().last().unwrap().chunk_type(), ChunkType::IEND);
+}
+
+#[test]
+fn encode_writes_to_separate_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = common::write_tiny_png(dir.path(), "in.png");
+    let output = dir.path().join("out.png");
+
+    pngme(&[
+        "encode",
+        path_str(&input),
+        "ruSt",
+        "secret",
+        "--output-file",
+        path_str(&output),
+    ])
+    .unwrap();
+
+    assert_eq!(fs::read(&input).unwrap(), common::TINY_PNG);
+    assert_eq!(
+        pngme(&["decode", path_str(&output), "ruSt"]).unwrap(),
+        "secret\n"
+    );
+}
+
+/// Runs the pngme binary with `stdin` piped in, returning what it wrote to stdout.
+fn pngme_piped(args: &[&str], stdin: &[u8]) -> Vec<u8> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pngme"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    output.stdout
+}
+
+#[test]
+fn encode_and_decode_through_stdio() {
+    let encoded = pngme_piped(
+        &["encode", "-", "ruSt", "secret", "-o", "-"],
+        &common::TINY_PNG,
+    );
+
+    let png = Png::try_from(encoded.as_slice()).unwrap();
+    assert_eq!(
+        png.chunk_by_type("ruSt").unwrap().data_as_string().unwrap(),
+        "secret"
+    );
+    assert_eq!(pngme_piped(&["decode", "-", "ruSt"], &encoded), b"secret\n");
+}
+
+#[test]
+fn encode_inserts_at_requested_index() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    pngme(&["encode", path_str(&path), "ruSt", "secret", "--index", "1"]).unwrap();
+
+    let png = Png::from_file(&path).unwrap();
+    assert_eq!(png.chunks()[1].chunk_type().to_string(), "ruSt");
+}
+
+#[test]
+fn encode_rejects_out_of_range_index() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    let err = pngme(&["encode", path_str(&path), "ruSt", "secret", "--index", "4"]).unwrap_err();
+
+    assert!(matches!(
+        err,
+        Error::Png(PngError::IndexOutOfRange { index: 4, len: 3 })
+    ));
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+}
+
+#[test]
+fn encode_refuses_critical_chunk_type() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    let err = pngme(&["encode", path_str(&path), "RuSt", "secret"]).unwrap_err();
+
+    assert!(matches!(err, Error::CriticalChunkType(t) if t.to_string() == "RuSt"));
+    assert!(err.to_string().contains("--force"));
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+}
+
+#[test]
+fn encode_force_allows_critical_chunk_type() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    pngme(&["encode", path_str(&path), "RuSt", "secret", "--force"]).unwrap();
+
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "RuSt"]).unwrap(),
+        "secret\n"
+    );
+}
+
+#[test]
+fn decode_prints_first_message() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "first"]).unwrap();
+    pngme(&["encode", path_str(&path), "ruSt", "second"]).unwrap();
+
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "ruSt"]).unwrap(),
+        "first\n"
+    );
+}
+
+#[test]
+fn decode_all_prints_every_message() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "first"]).unwrap();
+    pngme(&["encode", path_str(&path), "ruSt", "second"]).unwrap();
+
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "ruSt", "--all"]).unwrap(),
+        "0: first\n1: second\n"
+    );
+}
+
+#[test]
+fn encode_hides_each_message_in_its_own_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "a", "b", "c"]).unwrap();
+
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "ruSt", "--all"]).unwrap(),
+        "0: a\n1: b\n2: c\n"
+    );
+    let png = Png::from_file(&path).unwrap();
+    assert_eq!(png.chunks().len(), 6);
+    assert_eq!(*png.chunks().last().unwrap().chunk_type(), ChunkType::IEND);
+}
+
+#[test]
+fn encode_batch_processes_a_directory() {
+    let input = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let first = common::write_tiny_png(input.path(), "a.png");
+    let second = common::write_tiny_png(input.path(), "b.PNG");
+    fs::write(input.path().join("notes.txt"), "not an image").unwrap();
+
+    let printed = pngme(&[
+        "encode",
+        path_str(input.path()),
+        "ruSt",
+        "secret",
+        "--output-dir",
+        path_str(output.path()),
+    ])
+    .unwrap();
+
+    assert!(printed.ends_with("Encoded 2 files\n"));
+    assert_eq!(fs::read(&first).unwrap(), common::TINY_PNG);
+    assert_eq!(fs::read(&second).unwrap(), common::TINY_PNG);
+    for name in ["a.png", "b.PNG"] {
+        let encoded = output.path().join(name);
+        assert_eq!(
+            pngme(&["decode", path_str(&encoded), "ruSt"]).unwrap(),
+            "secret\n"
+        );
+    }
+    assert!(!output.path().join("notes.txt").exists());
+}
+
+#[test]
+fn encode_batch_reports_failures_without_aborting() {
+    let input = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    fs::write(input.path().join("a.png"), b"not a png").unwrap();
+    common::write_tiny_png(input.path(), "b.png");
+
+    let err = pngme(&[
+        "encode",
+        path_str(input.path()),
+        "ruSt",
+        "secret",
+        "--output-dir",
+        path_str(output.path()),
+    ])
+    .unwrap_err();
+
+    assert!(matches!(
+        err,
+        Error::BatchFailed {
+            failed: 1,
+            total: 2
+        }
+    ));
+    assert!(!output.path().join("a.png").exists());
+    assert_eq!(
+        pngme(&["decode", path_str(&output.path().join("b.png")), "ruSt"]).unwrap(),
+        "secret\n"
+    );
+}
+
+#[test]
+fn encode_batch_reports_failures_on_stderr() {
+    let input = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let broken = input.path().join("a.png");
+    fs::write(&broken, b"not a png").unwrap();
+    common::write_tiny_png(input.path(), "b.png");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_pngme"))
+        .args([
+            "encode",
+            path_str(input.path()),
+            "ruSt",
+            "secret",
+            "--output-dir",
+            path_str(output.path()),
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!result.status.success());
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(!stdout.contains("a.png"));
+    assert!(stdout.contains("b.png: encoded into"));
+    assert!(stderr.starts_with(&format!(
+        "{}: Input does not start with the PNG signature\n",
+        broken.display()
+    )));
+    assert!(stderr.ends_with("Error: Failed to encode 1 of 2 files\n"));
+    assert!(output.path().join("b.png").exists());
+}
+
+#[test]
+fn compressed_message_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    let message = "to be or not to be, ".repeat(200);
+
+    pngme(&["encode", path_str(&path), "ruSt", &message, "--compress"]).unwrap();
+
+    let png = Png::from_file(&path).unwrap();
+    assert!(pngme::payload::is_compressed(
+        png.chunk_by_type("ruSt").unwrap().data()
+    ));
+    assert!(png.byte_size() < common::TINY_PNG.len() + message.len() / 10);
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "ruSt"]).unwrap(),
+        format!("{}\n", message)
+    );
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn encrypted_message_round_trips() {
+    use pngme::payload::PayloadError;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&[
+        "encode",
+        path_str(&path),
+        "ruSt",
+        "secret",
+        "--compress",
+        "--passphrase",
+        "hunter2",
+    ])
+    .unwrap();
+
+    let png = Png::from_file(&path).unwrap();
+    assert!(pngme::payload::is_encrypted(
+        png.chunk_by_type("ruSt").unwrap().data()
+    ));
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "ruSt", "--passphrase", "hunter2"]).unwrap(),
+        "secret\n"
+    );
+
+    let err = pngme(&["decode", path_str(&path), "ruSt", "--passphrase", "nope"]).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Payload(PayloadError::DecryptionFailed)
+    ));
+    let err = pngme(&["decode", path_str(&path), "ruSt"]).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Payload(PayloadError::PassphraseRequired)
+    ));
+}
+
+#[cfg(not(feature = "encryption"))]
+#[test]
+fn encrypted_message_is_unsupported_without_feature() {
+    use pngme::payload::PayloadError;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    let mut png = Png::from_file(&path).unwrap();
+    let mut data = b"\0pngme:chacha20poly1305\0".to_vec();
+    data.extend_from_slice(&[0; 40]);
+    png.add_chunk_before_iend(Chunk::new(ChunkType::from_str("ruSt").unwrap(), data))
+        .unwrap();
+    png.write_file(&path).unwrap();
+    assert!(pngme::payload::is_encrypted(
+        png.chunk_by_type("ruSt").unwrap().data()
+    ));
+
+    let err = pngme(&["decode", path_str(&path), "ruSt"]).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Payload(PayloadError::EncryptionUnsupported)
+    ));
+}
+
+#[test]
+fn binary_message_file_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    let blob: Vec<u8> = (0..=255).rev().collect();
+    let input = dir.path().join("blob.bin");
+    let output = dir.path().join("out.bin");
+    fs::write(&input, &blob).unwrap();
+
+    pngme(&[
+        "encode",
+        path_str(&path),
+        "ruSt",
+        "--message-file",
+        path_str(&input),
+        "--compress",
+    ])
+    .unwrap();
+
+    assert_eq!(
+        pngme(&[
+            "decode",
+            path_str(&path),
+            "ruSt",
+            "--output-file",
+            path_str(&output)
+        ])
+        .unwrap(),
+        format!("Wrote 256 bytes to {}\n", output.display())
+    );
+    assert_eq!(fs::read(&output).unwrap(), blob);
+    assert_eq!(
+        pngme_piped(
+            &["decode", "-", "ruSt", "--output-file", "-"],
+            &fs::read(&path).unwrap()
+        ),
+        blob
+    );
+}
+
+#[test]
+fn message_file_starting_with_a_marker_needs_compress() {
+    use pngme::payload::PayloadError;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    let blob = b"\0pngme:deflate\0not really compressed".to_vec();
+    let input = dir.path().join("blob.bin");
+    let output = dir.path().join("out.bin");
+    fs::write(&input, &blob).unwrap();
+    let args = [
+        "encode",
+        path_str(&path),
+        "ruSt",
+        "--message-file",
+        path_str(&input),
+    ];
+
+    let err = pngme(&args).unwrap_err();
+    assert!(matches!(err, Error::Payload(PayloadError::MarkerPrefix)));
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+
+    pngme(&[&args[..], &["--compress"]].concat()).unwrap();
+    pngme(&[
+        "decode",
+        path_str(&path),
+        "ruSt",
+        "--output-file",
+        path_str(&output),
+    ])
+    .unwrap();
+    assert_eq!(fs::read(&output).unwrap(), blob);
+}
+
+#[test]
+fn decode_reports_missing_message() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "ruSt"]).unwrap(),
+        "No message found in a ruSt chunk\n"
+    );
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "ruSt", "--all"]).unwrap(),
+        "No message found in a ruSt chunk\n"
+    );
+}
+
+#[test]
+fn decode_rejects_non_utf8_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("binary.png");
+    let mut png = Png::try_from(&common::TINY_PNG[..]).unwrap();
+    png.insert_chunk(
+        2,
+        Chunk::new(ChunkType::from_str("ruSt").unwrap(), vec![0xff, 0xfe]),
+    )
+    .unwrap();
+    png.write_file(&path).unwrap();
+
+    let err = pngme(&["decode", path_str(&path), "ruSt"]).unwrap_err();
+    assert!(matches!(err, Error::Chunk(ChunkError::InvalidUtf8)));
+}
+
+#[test]
+fn remove_deletes_encoded_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "secret"]).unwrap();
+
+    let out = pngme(&["remove", path_str(&path), "ruSt"]).unwrap();
+
+    assert!(out.starts_with("Removed Chunk { type: ruSt"));
+    let png = Png::from_file(&path).unwrap();
+    assert!(png.chunk_by_type("ruSt").is_none());
+    assert_eq!(png.as_bytes(), common::TINY_PNG);
+}
+
+#[test]
+fn remove_deletes_several_chunk_types() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "one"]).unwrap();
+    pngme(&["encode", path_str(&path), "teXt", "two"]).unwrap();
+
+    let out = pngme(&["remove", path_str(&path), "ruSt", "foOb", "teXt"]).unwrap();
+
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("Removed Chunk { type: ruSt"));
+    assert!(lines[1].starts_with("Removed Chunk { type: teXt"));
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+}
+
+#[test]
+fn remove_ignore_case_repeats_match_successive_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "one"]).unwrap();
+    pngme(&["encode", path_str(&path), "RUST", "two", "--force"]).unwrap();
+
+    let out = pngme(&["remove", path_str(&path), "rust", "rust", "--ignore-case"]).unwrap();
+
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("Removed Chunk { type: ruSt"));
+    assert!(lines[1].starts_with("Removed Chunk { type: RUST"));
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+}
+
+#[test]
+fn remove_through_stdio_reports_missing_types_on_stderr() {
+    let encoded = pngme_piped(
+        &["encode", "-", "ruSt", "secret", "-o", "-"],
+        &common::TINY_PNG,
+    );
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pngme"))
+        .args(["remove", "-", "ruSt", "foOb"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&encoded).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, common::TINY_PNG);
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "No chunk of type foOb found\n"
+    );
+}
+
+#[test]
+fn quiet_remove_still_reports_missing_types_on_stderr() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "secret"]).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pngme"))
+        .args(["-q", "remove", path_str(&path), "ruSt", "foOb"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "No chunk of type foOb found\n"
+    );
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+}
+
+#[test]
+fn remove_fails_for_missing_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    let err = pngme(&["remove", path_str(&path), "ruSt"]).unwrap_err();
+    assert!(matches!(err, Error::Png(PngError::ChunkNotFound(_))));
+}
+
+#[test]
+fn print_lists_every_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "secret"]).unwrap();
+
+    assert_eq!(
+        pngme(&["print", path_str(&path)]).unwrap(),
+        "1x1 pixels, bit depth 8, color: Grayscale, interlace: None\n\
+         IHDR          13 bytes  critical   Image header\n\
+         IDAT          10 bytes  critical   Image data\n\
+         ruSt           6 bytes  ancillary\n\
+         IEND           0 bytes  critical   Image trailer\n"
+    );
+}
+
+#[test]
+fn print_filters_by_type() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "secret"]).unwrap();
+
+    assert_eq!(
+        pngme(&["print", path_str(&path), "--type", "ruSt"]).unwrap(),
+        "1x1 pixels, bit depth 8, color: Grayscale, interlace: None\n\
+         ruSt           6 bytes  ancillary\n"
+    );
+    let out = pngme(&["print", path_str(&path), "--type", "IDAT", "--type", "IEND"]).unwrap();
+    let types: Vec<&str> = out.lines().skip(1).map(|line| &line[..4]).collect();
+    assert_eq!(types, ["IDAT", "IEND"]);
+}
+
+#[test]
+fn print_formats_large_lengths() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    let message = "x".repeat(2048);
+    pngme(&["encode", path_str(&path), "ruSt", &message]).unwrap();
+
+    let out = pngme(&["print", path_str(&path)]).unwrap();
+    assert_eq!(
+        out.lines().nth(3),
+        Some("ruSt           2.0 KiB  ancillary")
+    );
+    let out = pngme(&["print", path_str(&path), "--bytes"]).unwrap();
+    assert_eq!(
+        out.lines().nth(3),
+        Some("ruSt        2048 bytes  ancillary")
+    );
+}
+
+#[test]
+fn print_reports_palette_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&[
+        "encode",
+        path_str(&path),
+        "PLTE",
+        "abcdef",
+        "--index",
+        "1",
+        "--force",
+    ])
+    .unwrap();
+
+    let out = pngme(&["print", path_str(&path)]).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[1], "Palette of 2 colors");
+    assert_eq!(lines[3], "PLTE           6 bytes  critical   Palette");
+}
+
+#[test]
+fn strip_removes_ancillary_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "one"]).unwrap();
+    pngme(&["encode", path_str(&path), "tEXt", "two", "--index", "1"]).unwrap();
+
+    assert_eq!(
+        pngme(&["strip", path_str(&path)]).unwrap(),
+        "Removed 2 ancillary chunks\n"
+    );
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+}
+
+#[test]
+fn quiet_suppresses_success_messages() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "one"]).unwrap();
+
+    assert_eq!(
+        pngme(&["--quiet", "remove", path_str(&path), "ruSt"]).unwrap(),
+        ""
+    );
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "ruSt", "-q"]).unwrap(),
+        "No message found in a ruSt chunk\n"
+    );
+}
+
+#[test]
+fn verbose_encode_logs_chunk_to_stderr() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pngme"))
+        .args(["--verbose", "encode", path_str(&path), "ruSt", "secret"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let crc = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"secret".to_vec()).crc();
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        format!("Encoding ruSt chunk: 6 bytes, CRC {:08x}\n", crc)
+    );
+}
+
+#[test]
+fn verbose_strip_logs_each_removed_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "one", "two"]).unwrap();
+    let png = Png::from_file(&path).unwrap();
+    let crcs: Vec<u32> = png.chunks_by_type("ruSt").iter().map(|c| c.crc()).collect();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pngme"))
+        .args(["strip", path_str(&path), "-v"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Removed 2 ancillary chunks\n");
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        format!(
+            "Stripping ruSt chunk: 3 bytes, CRC {:08x}\n\
+             Stripping ruSt chunk: 3 bytes, CRC {:08x}\n",
+            crcs[0], crcs[1]
+        )
+    );
+}
+
+#[test]
+fn rename_changes_chunk_type() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "one", "two"]).unwrap();
+
+    assert_eq!(
+        pngme(&["rename", path_str(&path), "ruSt", "teXt"]).unwrap(),
+        "Renamed 2 ruSt chunks to teXt\n"
+    );
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "teXt", "--all"]).unwrap(),
+        "0: one\n1: two\n"
+    );
+    assert!(Png::from_file(&path)
+        .unwrap()
+        .chunk_by_type("ruSt")
+        .is_none());
+
+    let err = pngme(&["rename", path_str(&path), "ruSt", "teXt"]).unwrap_err();
+    assert!(matches!(err, Error::Png(PngError::ChunkNotFound(_))));
+}
+
+#[test]
+fn repair_fixes_corrupted_crc() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("corrupt.png");
+    let mut bytes = common::TINY_PNG.to_vec();
+    bytes[52] ^= 0xff;
+    fs::write(&path, bytes).unwrap();
+    assert!(Png::from_file(&path).is_err());
+
+    assert_eq!(
+        pngme(&["repair", path_str(&path)]).unwrap(),
+        "Fixed 1 chunk CRCs\n"
+    );
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+    assert_eq!(
+        pngme(&["repair", path_str(&path)]).unwrap(),
+        "Fixed 0 chunk CRCs\n"
+    );
+}
+
+#[test]
+fn count_tallies_chunk_types() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "one"]).unwrap();
+    pngme(&["encode", path_str(&path), "ruSt", "two"]).unwrap();
+
+    assert_eq!(
+        pngme(&["count", path_str(&path)]).unwrap(),
+        "IDAT       1\n\
+         IEND       1\n\
+         IHDR       1\n\
+         ruSt       2\n\
+         Total      5\n"
+    );
+}
+
+#[test]
+fn meta_describes_every_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    assert_eq!(
+        pngme(&["meta", path_str(&path)]).unwrap(),
+        "IHDR          13 bytes  crc 3a7e9b55  critical  public  utf8\n\
+         IDAT          10 bytes  crc 48afa471  critical  public  binary\n\
+         IEND           0 bytes  crc ae426082  critical  public  utf8\n"
+    );
+}
+
+#[test]
+fn meta_json_emits_chunk_descriptors() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "secret"]).unwrap();
+
+    let out = pngme(&["meta", path_str(&path), "--json"]).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+    assert_eq!(json.as_array().unwrap().len(), 4);
+    assert_eq!(
+        json[2],
+        serde_json::json!({
+            "type": "ruSt",
+            "length": 6,
+            "crc": format!("{:08x}", Png::from_file(&path).unwrap().chunks()[2].crc()),
+            "critical": false,
+            "public": false,
+            "utf8": true,
+        })
+    );
+    assert_eq!(json[1]["utf8"], false);
+    assert_eq!(json[3]["crc"], "ae426082");
+    assert!(out.starts_with("[\n  {\n    \"type\": \"IHDR\",\n"));
+}
+
+#[test]
+fn verify_reports_ok_for_valid_png() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+
+    assert_eq!(pngme(&["verify", path_str(&path)]).unwrap(), "OK\n");
+}
+
+#[test]
+fn verify_lists_every_problem() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("corrupt.png");
+    let mut bytes = common::TINY_PNG.to_vec();
+    bytes[30] ^= 0xff;
+    bytes.truncate(bytes.len() - 12);
+    fs::write(&path, bytes).unwrap();
+
+    let cli = Cli::try_parse_from(["pngme", "verify", path_str(&path)]).unwrap();
+    let mut out = Vec::new();
+    let err = commands::run(&cli.command, cli.verbosity(), &mut out).unwrap_err();
+
+    assert!(matches!(err, Error::Png(PngError::VerificationFailed(2))));
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("CRC mismatch in chunk at byte 8"));
+    assert_eq!(lines[1], "PNG does not end with an IEND chunk");
+}
+
+#[test]
+fn find_reports_matching_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "a needle here"]).unwrap();
+
+    assert_eq!(
+        pngme(&["find", path_str(&path), "needle"]).unwrap(),
+        "chunk 2  ruSt  offset 2\n"
+    );
+    assert_eq!(
+        pngme(&["find", path_str(&path), "haystack"]).unwrap(),
+        "No chunk contains \"haystack\"\n"
+    );
+}
+
+#[test]
+fn diff_reports_added_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let original = common::write_tiny_png(dir.path(), "a.png");
+    let edited = dir.path().join("b.png");
+    pngme(&[
+        "encode",
+        path_str(&original),
+        "ruSt",
+        "secret",
+        "-o",
+        path_str(&edited),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        pngme(&["diff", path_str(&original), path_str(&edited)]).unwrap(),
+        "added     ruSt[0]\n"
+    );
+    assert_eq!(
+        pngme(&["diff", path_str(&edited), path_str(&original)]).unwrap(),
+        "removed   ruSt[0]\n"
+    );
+    assert_eq!(
+        pngme(&["diff", path_str(&original), path_str(&original)]).unwrap(),
+        "No differences\n"
+    );
+}
+
+#[test]
+fn extract_writes_raw_chunk_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    let blob: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let input = dir.path().join("blob.bin");
+    let output = dir.path().join("out.bin");
+    fs::write(&input, &blob).unwrap();
+    pngme(&[
+        "encode",
+        path_str(&path),
+        "ruSt",
+        "--message-file",
+        path_str(&input),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        pngme(&["extract", path_str(&path), "ruSt", path_str(&output)]).unwrap(),
+        format!("Wrote 1000 bytes to {}\n", output.display())
+    );
+    assert_eq!(fs::read(&output).unwrap(), blob);
+}
+
+#[test]
+fn extract_fails_for_missing_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    let output = dir.path().join("out.bin");
+
+    let err = pngme(&["extract", path_str(&path), "ruSt", path_str(&output)]).unwrap_err();
+
+    assert!(matches!(err, Error::Png(PngError::ChunkNotFound(_))));
+    assert!(!output.exists());
+}
+
+#[test]
+fn ignore_case_matches_any_case() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = common::write_tiny_png(dir.path(), "tiny.png");
+    pngme(&["encode", path_str(&path), "ruSt", "secret"]).unwrap();
+
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "rust"]).unwrap(),
+        "No message found in a rust chunk\n"
+    );
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "rust", "--ignore-case"]).unwrap(),
+        "secret\n"
+    );
+    assert_eq!(
+        pngme(&["decode", path_str(&path), "rust", "--ignore-case", "--all"]).unwrap(),
+        "0: secret\n"
+    );
+
+    let out = pngme(&["remove", path_str(&path), "rust", "--ignore-case"]).unwrap();
+    assert!(out.starts_with("Removed Chunk { type: ruSt"));
+    assert_eq!(fs::read(&path).unwrap(), common::TINY_PNG);
+}
@@ -0,0 +1,17 @@
+use std::path::{Path, PathBuf};
+
+/// A valid 1x1 grayscale image: IHDR, IDAT, IEND.
+#[rustfmt::skip]
+pub const TINY_PNG: [u8; 67] = [
+    137, 80, 78, 71, 13, 10, 26, 10, // signature
+    0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0, 58, 126, 155, 85, // IHDR
+    0, 0, 0, 10, 73, 68, 65, 84, 120, 156, 99, 96, 0, 0, 0, 2, 0, 1, 72, 175, 164, 113, // IDAT
+    0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130, // IEND
+];
+
+/// Writes [`TINY_PNG`] to `name` inside `dir` and returns its path.
+pub fn write_tiny_png(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, TINY_PNG).unwrap();
+    path
+}